@@ -0,0 +1,115 @@
+//! Compares the spatial grid broad phase with testing every bullet against every asteroid.
+//!
+//! Run with `cargo bench --bench broad_phase`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use asteroids::{Arena, Collider, SpatialGrid};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const ARENA: Arena = Arena {
+    width: 1280.,
+    height: 720.,
+};
+const ITERATIONS: u32 = 50;
+
+struct Body {
+    entity: Entity,
+    transform: Transform,
+    collider: Collider,
+}
+
+fn bodies(rng: &mut StdRng, count: u32, first_index: u32, radii: &[f32]) -> Vec<Body> {
+    (0..count)
+        .map(|index| Body {
+            entity: Entity::from_raw(first_index + index),
+            transform: Transform::from_xyz(
+                rng.gen_range(-ARENA.width / 2.0..ARENA.width / 2.),
+                rng.gen_range(-ARENA.height / 2.0..ARENA.height / 2.),
+                0.,
+            ),
+            collider: Collider::Circle(radii[index as usize % radii.len()]),
+        })
+        .collect()
+}
+
+fn brute_force(bullets: &[Body], asteroids: &[Body]) -> usize {
+    bullets
+        .iter()
+        .flat_map(|bullet| asteroids.iter().map(move |asteroid| (bullet, asteroid)))
+        .filter(|(bullet, asteroid)| {
+            bullet
+                .collider
+                .intersects(&bullet.transform, &asteroid.collider, &asteroid.transform)
+        })
+        .count()
+}
+
+fn spatial_grid(grid: &mut SpatialGrid, bullets: &[Body], asteroids: &[Body]) -> usize {
+    grid.reset(&ARENA, 80.);
+    for body in bullets.iter().chain(asteroids) {
+        grid.insert(
+            body.entity,
+            body.transform.translation.truncate(),
+            body.collider.bounding_radius(),
+        );
+    }
+
+    let first_asteroid = asteroids[0].entity.index();
+    bullets
+        .iter()
+        .map(|bullet| {
+            grid.candidates(
+                bullet.transform.translation.truncate(),
+                bullet.collider.bounding_radius(),
+            )
+            .filter_map(|entity| entity.index().checked_sub(first_asteroid))
+            .map(|index| &asteroids[index as usize])
+            .filter(|asteroid| {
+                bullet.collider.intersects(
+                    &bullet.transform,
+                    &asteroid.collider,
+                    &asteroid.transform,
+                )
+            })
+            .count()
+        })
+        .sum()
+}
+
+fn time(mut run: impl FnMut() -> usize) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    for _ in 0..ITERATIONS {
+        hits = black_box(run());
+    }
+    (start.elapsed() / ITERATIONS, hits)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut grid = SpatialGrid::new(&ARENA, 80.);
+
+    println!(
+        "{:>9} {:>7} {:>14} {:>14}",
+        "asteroids", "bullets", "brute force", "spatial grid"
+    );
+
+    for (asteroid_count, bullet_count) in [(10, 4), (100, 50), (500, 200), (2000, 500)] {
+        let bullets = bodies(&mut rng, bullet_count, 0, &[2.]);
+        let asteroids = bodies(&mut rng, asteroid_count, bullet_count, &[40., 20., 10.]);
+
+        let (brute_force_time, brute_force_hits) = time(|| brute_force(&bullets, &asteroids));
+        let (grid_time, grid_hits) = time(|| spatial_grid(&mut grid, &bullets, &asteroids));
+        assert_eq!(brute_force_hits, grid_hits);
+
+        println!(
+            "{:>9} {:>7} {:>14?} {:>14?}",
+            asteroid_count, bullet_count, brute_force_time, grid_time
+        );
+    }
+}
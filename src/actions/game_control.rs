@@ -1,35 +1,182 @@
-use bevy::prelude::{ButtonInput, KeyCode, Res};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
 
+use crate::actions::gamepad::GamepadInput;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GameControl {
-    Up,
-    Down,
-    Left,
-    Right,
+    RotateLeft,
+    RotateRight,
+    Thrust,
+    Hyperspace,
+    Shield,
+    Fire,
+    Pause,
 }
 
 impl GameControl {
-    pub fn pressed(&self, keyboard_input: &Res<ButtonInput<KeyCode>>) -> bool {
+    pub const ALL: [GameControl; 7] = [
+        GameControl::RotateLeft,
+        GameControl::RotateRight,
+        GameControl::Thrust,
+        GameControl::Hyperspace,
+        GameControl::Shield,
+        GameControl::Fire,
+        GameControl::Pause,
+    ];
+
+    pub fn label(&self) -> &'static str {
         match self {
-            GameControl::Up => {
-                keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp)
-            }
-            GameControl::Down => {
-                keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown)
-            }
-            GameControl::Left => {
-                keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft)
+            GameControl::RotateLeft => "Rotate left",
+            GameControl::RotateRight => "Rotate right",
+            GameControl::Thrust => "Thrust",
+            GameControl::Hyperspace => "Hyperspace",
+            GameControl::Shield => "Shield",
+            GameControl::Fire => "Fire",
+            GameControl::Pause => "Pause",
+        }
+    }
+
+    pub fn pressed(&self, bindings: &KeyBindings, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        keyboard_input.any_pressed(bindings.keys(*self).iter().copied())
+    }
+
+    pub fn just_pressed(
+        &self,
+        bindings: &KeyBindings,
+        keyboard_input: &ButtonInput<KeyCode>,
+    ) -> bool {
+        keyboard_input.any_just_pressed(bindings.keys(*self).iter().copied())
+    }
+}
+
+/// The keys bound to each `GameControl`. Any of the keys bound to a control works.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub rotate_left: Vec<KeyCode>,
+    pub rotate_right: Vec<KeyCode>,
+    pub thrust: Vec<KeyCode>,
+    pub hyperspace: Vec<KeyCode>,
+    pub shield: Vec<KeyCode>,
+    pub fire: Vec<KeyCode>,
+    pub pause: Vec<KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            rotate_left: vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+            rotate_right: vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            thrust: vec![KeyCode::KeyW, KeyCode::ArrowUp],
+            hyperspace: vec![KeyCode::KeyS, KeyCode::ArrowDown],
+            shield: vec![KeyCode::KeyE, KeyCode::ShiftLeft],
+            fire: vec![KeyCode::Space],
+            pause: vec![KeyCode::Escape, KeyCode::KeyP],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, control: GameControl) -> &[KeyCode] {
+        match control {
+            GameControl::RotateLeft => &self.rotate_left,
+            GameControl::RotateRight => &self.rotate_right,
+            GameControl::Thrust => &self.thrust,
+            GameControl::Hyperspace => &self.hyperspace,
+            GameControl::Shield => &self.shield,
+            GameControl::Fire => &self.fire,
+            GameControl::Pause => &self.pause,
+        }
+    }
+
+    fn keys_mut(&mut self, control: GameControl) -> &mut Vec<KeyCode> {
+        match control {
+            GameControl::RotateLeft => &mut self.rotate_left,
+            GameControl::RotateRight => &mut self.rotate_right,
+            GameControl::Thrust => &mut self.thrust,
+            GameControl::Hyperspace => &mut self.hyperspace,
+            GameControl::Shield => &mut self.shield,
+            GameControl::Fire => &mut self.fire,
+            GameControl::Pause => &mut self.pause,
+        }
+    }
+
+    /// Makes the key the only one for the control, taking it away from any other control. A
+    /// control that would be left without keys gets the control's old keys in exchange.
+    ///
+    /// Refuses, leaving the bindings as they are, when some other control would still lose its last
+    /// key. Returns whether the key was bound.
+    pub fn bind(&mut self, control: GameControl, key: KeyCode) -> bool {
+        let mut bindings = self.clone();
+        let mut old_keys = std::mem::replace(bindings.keys_mut(control), vec![key]);
+        old_keys.retain(|&bound| bound != key);
+
+        for other in GameControl::ALL {
+            if other == control {
+                continue;
             }
-            GameControl::Right => {
-                keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight)
+
+            let keys = bindings.keys_mut(other);
+            if keys.contains(&key) {
+                keys.retain(|&bound| bound != key);
+                if keys.is_empty() {
+                    *keys = std::mem::take(&mut old_keys);
+                }
             }
         }
+
+        let emptied = GameControl::ALL
+            .into_iter()
+            .find(|&other| !self.keys(other).is_empty() && bindings.keys(other).is_empty());
+        if let Some(emptied) = emptied {
+            warn!(
+                "Not binding {:?}, it would leave {:?} without a key",
+                key, emptied
+            );
+            return false;
+        }
+        *self = bindings;
+        true
     }
 }
 
-pub fn get_movement(control: GameControl, input: &Res<ButtonInput<KeyCode>>) -> f32 {
-    if control.pressed(input) {
-        1.0
-    } else {
-        0.0
+/// Reads the controls from the keyboard, through the key bindings, and from the active gamepad
+#[derive(SystemParam)]
+pub struct Controls<'w> {
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    bindings: Res<'w, KeyBindings>,
+    gamepad: GamepadInput<'w>,
+}
+
+impl Controls<'_> {
+    pub fn pressed(&self, control: GameControl) -> bool {
+        control.pressed(&self.bindings, &self.keyboard_input) || self.gamepad.pressed(control)
+    }
+
+    pub fn just_pressed(&self, control: GameControl) -> bool {
+        control.just_pressed(&self.bindings, &self.keyboard_input)
+            || self.gamepad.just_pressed(control)
+    }
+
+    /// Rotation on x, thrust on y. The keyboard takes precedence over the gamepad.
+    pub fn movement(&self) -> Vec2 {
+        let key = |control: GameControl| {
+            if control.pressed(&self.bindings, &self.keyboard_input) {
+                1.
+            } else {
+                0.
+            }
+        };
+        let keyboard = Vec2::new(
+            key(GameControl::RotateRight) - key(GameControl::RotateLeft),
+            key(GameControl::Thrust),
+        );
+
+        if keyboard != Vec2::ZERO {
+            keyboard.normalize()
+        } else {
+            self.gamepad.movement()
+        }
     }
 }
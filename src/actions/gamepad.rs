@@ -0,0 +1,145 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::gamepad::{GamepadConnectionEvent, Gamepads},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::GameControl;
+
+const DEFAULT_DEAD_ZONE: f32 = 0.15;
+
+/// How the gamepad is read
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct GamepadConfig {
+    /// How far, from 0 to 1, the stick or a trigger has to move before it counts
+    pub dead_zone: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            dead_zone: DEFAULT_DEAD_ZONE,
+        }
+    }
+}
+
+/// The gamepad the ship is flown with. The first one connected, until it is disconnected.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+impl GameControl {
+    fn gamepad_buttons(&self) -> &'static [GamepadButtonType] {
+        match self {
+            GameControl::RotateLeft => &[GamepadButtonType::DPadLeft],
+            GameControl::RotateRight => &[GamepadButtonType::DPadRight],
+            GameControl::Thrust => &[GamepadButtonType::RightTrigger2],
+            GameControl::Hyperspace => &[GamepadButtonType::DPadDown, GamepadButtonType::East],
+            GameControl::Shield => &[GamepadButtonType::LeftTrigger2, GamepadButtonType::West],
+            GameControl::Fire => &[GamepadButtonType::South],
+            GameControl::Pause => &[GamepadButtonType::Start],
+        }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct GamepadInput<'w> {
+    active: Res<'w, ActiveGamepad>,
+    config: Res<'w, GamepadConfig>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+    button_axes: Res<'w, Axis<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl GamepadInput<'_> {
+    pub fn pressed(&self, control: GameControl) -> bool {
+        self.active.0.is_some_and(|gamepad| {
+            self.buttons.any_pressed(
+                control
+                    .gamepad_buttons()
+                    .iter()
+                    .map(|&button_type| GamepadButton::new(gamepad, button_type)),
+            )
+        })
+    }
+
+    pub fn just_pressed(&self, control: GameControl) -> bool {
+        self.active.0.is_some_and(|gamepad| {
+            self.buttons.any_just_pressed(
+                control
+                    .gamepad_buttons()
+                    .iter()
+                    .map(|&button_type| GamepadButton::new(gamepad, button_type)),
+            )
+        })
+    }
+
+    /// Rotation and thrust like `Actions::player_movement`, but not normalized: how far the stick
+    /// and the trigger are pushed scales how fast the ship turns and speeds up
+    pub fn movement(&self) -> Vec2 {
+        let Some(gamepad) = self.active.0 else {
+            return Vec2::ZERO;
+        };
+
+        let rotation = match (
+            self.pressed(GameControl::RotateLeft),
+            self.pressed(GameControl::RotateRight),
+        ) {
+            (true, false) => -1.,
+            (false, true) => 1.,
+            _ => self.beyond_dead_zone(
+                self.axes
+                    .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or(0.),
+            ),
+        };
+
+        // not every gamepad backend reports how far the trigger is pulled
+        let trigger = self.beyond_dead_zone(
+            self.button_axes
+                .get(GamepadButton::new(
+                    gamepad,
+                    GamepadButtonType::RightTrigger2,
+                ))
+                .unwrap_or(0.),
+        );
+        let thrust = if trigger > 0. {
+            trigger
+        } else if self.pressed(GameControl::Thrust) {
+            1.
+        } else {
+            0.
+        };
+
+        Vec2::new(rotation, thrust)
+    }
+
+    // Scales what is left past the dead zone back to the full range
+    fn beyond_dead_zone(&self, value: f32) -> f32 {
+        let dead_zone = self.config.dead_zone.clamp(0., 0.99);
+        if value.abs() <= dead_zone {
+            0.
+        } else {
+            value.signum() * (value.abs() - dead_zone) / (1. - dead_zone)
+        }
+    }
+}
+
+pub fn track_gamepads(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for event in connection_events.read() {
+        if event.connected() {
+            if active.0.is_none() {
+                info!("Flying with gamepad {:?}", event.gamepad);
+                active.0 = Some(event.gamepad);
+            }
+        } else if active.0 == Some(event.gamepad) {
+            // carry on with another gamepad, if there is one
+            active.0 = gamepads.iter().find(|&gamepad| gamepad != event.gamepad);
+        }
+    }
+}
@@ -1,22 +1,35 @@
-use bevy::prelude::*;
+use bevy::{input::InputSystem, prelude::*};
 
-use crate::actions::game_control::{get_movement, GameControl};
+use crate::replay::Playback;
 use crate::ship::Ship;
-use crate::{GameState, Heading, Position};
+use crate::{Collider, Heading, PauseState, Position};
 
 mod game_control;
+mod gamepad;
+
+pub(crate) use game_control::Controls;
+pub use game_control::{GameControl, KeyBindings};
+pub(crate) use gamepad::track_gamepads;
+pub use gamepad::{ActiveGamepad, GamepadConfig};
 
 pub struct ActionsPlugin;
 
-// This plugin listens for keyboard input and converts the input into Actions
+// This plugin listens for keyboard and gamepad input and converts the input into Actions
 // Actions can then be used as a resource in other systems to act on the player input.
+// While a replay is played back, the replay provides the actions instead of the keyboard.
 impl Plugin for ActionsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Actions>()
+        app.init_resource::<KeyBindings>()
+            .init_resource::<GamepadConfig>()
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<Actions>()
             .init_resource::<FiredAction>()
+            .add_systems(PreUpdate, track_gamepads.after(InputSystem))
             .add_systems(
                 Update,
-                (set_movement_actions, set_fired_actions).run_if(in_state(GameState::Playing)),
+                (set_movement_actions, set_fired_actions)
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(not(resource_exists::<Playback>)),
             );
     }
 }
@@ -24,44 +37,45 @@ impl Plugin for ActionsPlugin {
 #[derive(Default, Resource)]
 pub struct Actions {
     pub player_movement: Option<Vec2>,
+    pub shield: bool,
+    /// Stays set until the ship has jumped, or found it can't, like `FiredAction`
+    pub hyperspace: bool,
 }
 
+// Stays set until the next fixed tick has seen it, fixed ticks don't run every frame. A shot that
+// tick can't fire, during the fire cooldown or with too many bullets out, is dropped, not saved up.
 #[derive(Default, Resource, Debug)]
 pub struct FiredAction {
     pub heading: Option<Heading>,
     pub position: Option<Position>,
 }
 
-pub fn set_movement_actions(
-    mut actions: ResMut<Actions>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-) {
-    let player_movement = Vec2::new(
-        get_movement(GameControl::Right, &keyboard_input)
-            - get_movement(GameControl::Left, &keyboard_input),
-        get_movement(GameControl::Up, &keyboard_input)
-            - get_movement(GameControl::Down, &keyboard_input),
-    );
+pub fn set_movement_actions(mut actions: ResMut<Actions>, controls: Controls) {
+    let player_movement = controls.movement();
 
     if player_movement != Vec2::ZERO {
-        actions.player_movement = Some(player_movement.normalize());
+        actions.player_movement = Some(player_movement);
     } else {
         actions.player_movement = None;
     }
+
+    actions.shield = controls.pressed(GameControl::Shield);
+    if controls.just_pressed(GameControl::Hyperspace) {
+        actions.hyperspace = true;
+    }
 }
 
+// Holding the button keeps firing, as fast as the ship's fire cooldown allows
 pub fn set_fired_actions(
     mut actions: ResMut<FiredAction>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    ship_query: Query<&Transform, With<Ship>>,
+    controls: Controls,
+    // a ship in hyperspace has no collider, and nothing to fire from
+    ship_query: Query<&Transform, (With<Ship>, With<Collider>)>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if controls.pressed(GameControl::Fire) {
         if let Ok(ship_transform) = ship_query.get_single() {
             actions.heading = Some(Heading(ship_transform.rotation * Vec3::Y));
             actions.position = Some(Position(ship_transform.translation.truncate()));
         }
-    } else {
-        actions.heading = None;
-        actions.position = None;
     }
 }
@@ -2,28 +2,38 @@ use std::{ops::Range, time::Duration};
 
 use bevy::prelude::*;
 use bevy_prototype_lyon::{draw::Fill, entity::ShapeBundle, prelude::GeometryBuilder, shapes};
-use rand::{prelude::thread_rng, Rng};
+use rand::Rng;
 
-use crate::{Collider, GameState, Hit, Position, Velocity, Wrapping};
+use crate::{
+    rng::GameRng,
+    waves::{reset_wave, DifficultyCurve, Wave},
+    Arena, Collider, GameState, Hit, Kinematics, Position, Velocity, Wrapping,
+};
 
 pub struct AsteroidsPlugin;
 
 impl Plugin for AsteroidsPlugin {
     fn build(&self, app: &mut App) {
         app.add_sub_state::<AsteroidsState>()
-            .add_systems(OnEnter(GameState::Menu), spawn_asteroids)
+            // quitting mid-game leaves the game's asteroids behind
+            .add_systems(
+                OnEnter(GameState::Menu),
+                (despawn_asteroids, spawn_asteroids.after(reset_wave)).chain(),
+            )
             .add_systems(OnExit(GameState::Menu), despawn_asteroids)
+            .add_systems(OnExit(GameState::GameOver), despawn_asteroids)
             .add_systems(OnEnter(AsteroidsState::Flying), spawn_asteroids)
             .add_systems(
-                Update,
+                FixedUpdate,
                 (check_level_complete).run_if(in_state(AsteroidsState::Flying)),
             )
-            .add_systems(FixedUpdate, (displace, handle_hit))
+            .add_systems(FixedUpdate, handle_hit.after(Kinematics))
             .add_systems(
-                Update,
+                FixedUpdate,
                 respawn_timer.run_if(in_state(AsteroidsState::Destroyed)),
             )
             .add_systems(OnEnter(AsteroidsState::Destroyed), start_respawn_timer)
+            .add_systems(OnExit(AsteroidsState::Destroyed), despawn_respawn_timer)
             .add_event::<BangLargeEvent>()
             .add_event::<BangMediumEvent>()
             .add_event::<BangSmallEvent>();
@@ -35,27 +45,28 @@ const ASTEROID_RADIUS_MEDIUM: f32 = 20.;
 const ASTEROID_RADIUS_SMALL: f32 = 10.;
 const ASTEROID_COLOR: Color = Color::WHITE;
 const ASTEROID_RESPAWN_TIME_IN_SECONDS: u64 = 4;
-const ASTEROID_SPAWN_RANGE: Range<i32> = 5..10;
+const ASTEROID_SPLIT_SPEED_RANGE: Range<f32> = 6.4..192.;
 
 #[derive(SubStates, Default, Clone, Eq, PartialEq, Debug, Hash)]
 #[source(GameState = GameState::Playing)]
-enum AsteroidsState {
+pub(crate) enum AsteroidsState {
     #[default]
     Flying,
     Destroyed,
 }
 
-#[derive(Event, Default)]
-pub(crate) struct BangLargeEvent;
-#[derive(Event, Default)]
-pub(crate) struct BangMediumEvent;
-#[derive(Event, Default)]
-pub(crate) struct BangSmallEvent;
+// The bangs are sent from where the asteroid was
+#[derive(Event)]
+pub(crate) struct BangLargeEvent(pub Vec2);
+#[derive(Event)]
+pub(crate) struct BangMediumEvent(pub Vec2);
+#[derive(Event)]
+pub(crate) struct BangSmallEvent(pub Vec2);
 
 #[derive(Component)]
 pub struct Asteroid;
 
-#[derive(Component)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AsteroidSize {
     Large,
     Medium,
@@ -73,7 +84,7 @@ impl AsteroidSize {
 }
 
 #[derive(Bundle)]
-struct AsteroidBundle {
+pub struct AsteroidBundle {
     shape: ShapeBundle,
     fill: Fill,
     asteroid: Asteroid,
@@ -84,7 +95,7 @@ struct AsteroidBundle {
 }
 
 impl AsteroidBundle {
-    fn new(position: Position, velocity: Velocity, size: AsteroidSize) -> Self {
+    pub fn new(position: Position, velocity: Velocity, size: AsteroidSize) -> Self {
         let shape = shapes::Circle {
             radius: size.radius(),
             center: Vec2::ZERO,
@@ -106,34 +117,38 @@ impl AsteroidBundle {
             asteroid: Asteroid,
             velocity,
             wrapping: Wrapping,
-            collider: Collider,
+            collider: Collider::Circle(size.radius()),
             size,
         }
     }
 
-    fn random_velocity(position: Position, size: AsteroidSize) -> Self {
-        Self::new(position, Velocity::random(), size)
+    fn random_velocity(position: Position, size: AsteroidSize, rng: &mut impl Rng) -> Self {
+        Self::new(
+            position,
+            Velocity::random(ASTEROID_SPLIT_SPEED_RANGE, rng),
+            size,
+        )
     }
 }
 
-fn spawn_asteroids(mut commands: Commands, window: Query<&Window>) {
-    info!("Spawning asteroids");
-    let window = window.single();
-
-    let half_width = window.width() / 2.;
-    let half_height = window.height() / 2.;
-
-    let rand_num_asteroids = thread_rng().gen_range(ASTEROID_SPAWN_RANGE);
+fn spawn_asteroids(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    wave: Res<Wave>,
+    difficulty: Res<DifficultyCurve>,
+    mut rng: ResMut<GameRng>,
+) {
+    info!("Spawning asteroids for wave {}", wave.0);
+    let half_extents = arena.half_extents();
 
-    for _ in 0..rand_num_asteroids {
-        let random_x: f32 = thread_rng().gen_range(half_width * -1.0..half_width);
-        let random_y: f32 = thread_rng().gen_range(half_height * -1.0..half_height);
+    for _ in 0..difficulty.asteroid_count(*wave) {
+        let random_x: f32 = rng.gen_range(-half_extents.x..half_extents.x);
+        let random_y: f32 = rng.gen_range(-half_extents.y..half_extents.y);
         let random_position = Position(Vec2::new(random_x, random_y));
 
         commands.spawn(AsteroidBundle::new(
             random_position,
-            // initial asteroids shouldn't be too fast
-            Velocity::random_with_speed(1.),
+            Velocity::random_with_speed(difficulty.asteroid_speed(*wave), &mut *rng),
             AsteroidSize::Large,
         ));
     }
@@ -145,46 +160,45 @@ fn despawn_asteroids(mut commands: Commands, asteroid_query: Query<Entity, With<
     }
 }
 
-fn displace(mut asteroid_query: Query<(&mut Transform, &Velocity), With<Asteroid>>) {
-    for (mut transform, velocity) in &mut asteroid_query {
-        let translation_delta = velocity.0;
-        transform.translation += translation_delta;
-    }
-}
-
 fn handle_hit(
     mut commands: Commands,
     hit_query: Query<(Entity, &AsteroidSize, &Transform, &Hit), With<Asteroid>>,
+    mut rng: ResMut<GameRng>,
     mut bang_large_event: EventWriter<BangLargeEvent>,
     mut bang_medium_event: EventWriter<BangMediumEvent>,
     mut bang_small_event: EventWriter<BangSmallEvent>,
 ) {
     for (entity, size, transform, _) in hit_query.iter() {
+        let position = transform.translation.truncate();
         match size {
             AsteroidSize::Large => {
                 commands.spawn(AsteroidBundle::random_velocity(
-                    Position(transform.translation.truncate()),
+                    Position(position),
                     AsteroidSize::Medium,
+                    &mut *rng,
                 ));
                 commands.spawn(AsteroidBundle::random_velocity(
-                    Position(transform.translation.truncate()),
+                    Position(position),
                     AsteroidSize::Medium,
+                    &mut *rng,
                 ));
-                bang_large_event.send_default();
+                bang_large_event.send(BangLargeEvent(position));
             }
             AsteroidSize::Medium => {
                 commands.spawn(AsteroidBundle::random_velocity(
-                    Position(transform.translation.truncate()),
+                    Position(position),
                     AsteroidSize::Small,
+                    &mut *rng,
                 ));
                 commands.spawn(AsteroidBundle::random_velocity(
-                    Position(transform.translation.truncate()),
+                    Position(position),
                     AsteroidSize::Small,
+                    &mut *rng,
                 ));
-                bang_medium_event.send_default();
+                bang_medium_event.send(BangMediumEvent(position));
             }
             _ => {
-                bang_small_event.send_default();
+                bang_small_event.send(BangSmallEvent(position));
             }
         }
 
@@ -212,6 +226,13 @@ fn start_respawn_timer(mut commands: Commands) {
     info!("Level complete")
 }
 
+// The timer is still running when the game ends between two waves
+fn despawn_respawn_timer(mut commands: Commands, timer_query: Query<Entity, With<RespawnTime>>) {
+    for entity in &timer_query {
+        commands.entity(entity).despawn();
+    }
+}
+
 fn respawn_timer(
     mut commands: Commands,
     time: Res<Time>,
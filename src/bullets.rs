@@ -1,48 +1,136 @@
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
-};
+use std::time::Duration;
+
+use bevy::{prelude::*, time::Stopwatch};
 use bevy_prototype_lyon::{draw::Fill, entity::ShapeBundle, prelude::GeometryBuilder, shapes};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    actions::FiredAction, asteroids::Asteroid, Collider, GameState, Heading, Hit, Position,
+    actions::FiredAction,
+    asteroids::Asteroid,
+    powerups::{ActivePowerUps, PowerUp},
+    ship::Ship,
+    Arena, Collider, CollisionSet, GameState, Heading, Hit, Kinematics, Position, SpatialGrid,
+    Velocity, Wrapping,
 };
 
 const BULLET_RADIUS: f32 = 2.;
-const BULLET_SPEED: f32 = 10.;
+const BULLET_SPEED: f32 = 640.;
+const BULLET_LIFETIME: Duration = Duration::from_millis(1500);
 const BULLET_COLOR: Color = Color::WHITE;
+// Angle between the bullets of a spread shot
+const SPREAD_ANGLE: f32 = 0.25;
+const FIRE_INTERVAL: Duration = Duration::from_millis(250);
+const RAPID_FIRE_INTERVAL: Duration = Duration::from_millis(100);
+// Like the arcade game
+const MAX_BULLETS: usize = 4;
+// Long enough for a piercing bullet to get through the largest asteroid
+const PIERCING_COOLDOWN: Duration = Duration::from_millis(150);
 
 pub struct BulletsPlugin;
 
 impl Plugin for BulletsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (spawn_bullet, detect_collisions, despawn_bullet).run_if(in_state(GameState::Playing)),
-        )
-        .add_systems(FixedUpdate, displace.run_if(in_state(GameState::Playing)))
-        .add_event::<BulletFiredEvent>();
+        app.init_resource::<BulletSettings>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_bullet.before(Kinematics),
+                    (travel, despawn_bullet).chain().after(Kinematics),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                detect_collisions
+                    .in_set(CollisionSet::NarrowPhase)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Menu), despawn_bullets)
+            .add_systems(OnExit(GameState::GameOver), despawn_bullets)
+            .add_event::<BulletFiredEvent>()
+            .add_event::<BulletHitEvent>();
     }
 }
 
+#[derive(Event)]
+pub(crate) struct BulletFiredEvent {
+    pub position: Vec2,
+}
+
+/// One of the ship's bullets hit something
 #[derive(Event, Default)]
-pub(crate) struct BulletFiredEvent;
+pub(crate) struct BulletHitEvent;
 
 #[derive(Component)]
 pub struct Bullet;
 
+/// A bullet fired at the ship rather than by it
+#[derive(Component)]
+pub struct EnemyBullet;
+
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BulletSettings {
+    /// Whether bullets wrap around the arena edges or fly off into the void
+    pub wrap: bool,
+    /// How long bullets fly before they disappear
+    pub lifetime: Duration,
+    /// Time between shots while the fire button is held
+    pub fire_interval: Duration,
+    /// Most bullets the ship can have in the arena at once
+    pub max_bullets: usize,
+}
+
+impl Default for BulletSettings {
+    fn default() -> Self {
+        Self {
+            wrap: true,
+            lifetime: BULLET_LIFETIME,
+            fire_interval: FIRE_INTERVAL,
+            max_bullets: MAX_BULLETS,
+        }
+    }
+}
+
+/// Time a bullet has left to fly. Change it to make a bullet last longer or shorter.
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    pub fn new(lifetime: Duration) -> Self {
+        Self(Timer::new(lifetime, TimerMode::Once))
+    }
+}
+
+// Time since a piercing bullet last hit something. It ignores asteroids for a moment after a hit,
+// so it doesn't hit the same asteroid, or its pieces, again while passing through.
+#[derive(Component)]
+struct Piercing(Stopwatch);
+
+impl Default for Piercing {
+    fn default() -> Self {
+        let mut since_hit = Stopwatch::new();
+        since_hit.set_elapsed(PIERCING_COOLDOWN);
+        Self(since_hit)
+    }
+}
+
+/// Time left before the ship can fire again
+#[derive(Component, Default)]
+pub struct FireCooldown(pub Timer);
+
 #[derive(Bundle)]
-struct BulletBundle {
+pub struct BulletBundle {
     shape: ShapeBundle,
     fill: Fill,
     bullet: Bullet,
-    heading: Heading,
-    origin: Position,
+    velocity: Velocity,
+    lifetime: Lifetime,
     collider: Collider,
 }
 
 impl BulletBundle {
-    fn new(heading: Heading, position: Position) -> Self {
+    pub fn new(heading: Heading, position: Position) -> Self {
         let shape = shapes::Circle {
             radius: BULLET_RADIUS,
             center: Vec2::ZERO,
@@ -63,46 +151,96 @@ impl BulletBundle {
             },
             fill: Fill::color(BULLET_COLOR),
             bullet: Bullet,
-            heading,
-            origin: position,
-            collider: Collider,
+            velocity: Velocity(heading.0 * BULLET_SPEED),
+            lifetime: Lifetime::new(BULLET_LIFETIME),
+            collider: Collider::Circle(BULLET_RADIUS),
         }
     }
+
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = Lifetime::new(lifetime);
+        self
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_bullet(
     mut commands: Commands,
-    actions: Res<FiredAction>,
+    time: Res<Time>,
+    settings: Res<BulletSettings>,
+    power_ups: Res<ActivePowerUps>,
+    mut cooldown_query: Query<&mut FireCooldown, (With<Ship>, With<Collider>)>,
+    bullet_query: Query<(), (With<Bullet>, Without<EnemyBullet>)>,
+    mut actions: ResMut<FiredAction>,
     mut bullet_fired: EventWriter<BulletFiredEvent>,
 ) {
-    if actions.heading.is_none() && actions.position.is_none() {
+    // nothing fires from hyperspace, and nothing fired then is left over for later
+    let Ok(mut cooldown) = cooldown_query.get_single_mut() else {
+        *actions = FiredAction::default();
+        return;
+    };
+    cooldown.0.tick(time.delta());
+
+    let (Some(heading), Some(position)) = (actions.heading.take(), actions.position.take()) else {
+        return;
+    };
+
+    if !cooldown.0.finished() {
         return;
     }
+    let rapid_fire = power_ups.is_active(PowerUp::RapidFire);
 
-    bullet_fired.send_default();
+    let angles: &[f32] = if power_ups.is_active(PowerUp::SpreadShot) {
+        &[-SPREAD_ANGLE, 0., SPREAD_ANGLE]
+    } else {
+        &[0.]
+    };
 
-    commands.spawn(BulletBundle::new(
-        actions.heading.unwrap(),
-        actions.position.unwrap(),
-    ));
-}
+    // the power-ups raise the cap, or they would hardly make a difference
+    let max_bullets = settings.max_bullets * angles.len() * if rapid_fire { 2 } else { 1 };
+    if bullet_query.iter().count() + angles.len() > max_bullets {
+        return;
+    }
 
-fn displace(mut bullet_query: Query<(&mut Transform, &Heading), With<Bullet>>) {
-    for (mut transform, heading) in &mut bullet_query {
-        let translation_delta = heading.0 * BULLET_SPEED;
-        transform.translation += translation_delta;
+    let interval = if rapid_fire {
+        settings.fire_interval.min(RAPID_FIRE_INTERVAL)
+    } else {
+        settings.fire_interval
+    };
+    cooldown.0 = Timer::new(interval, TimerMode::Once);
+
+    for &angle in angles {
+        bullet_fired.send(BulletFiredEvent {
+            position: position.0,
+        });
+        let heading = Heading(Quat::from_rotation_z(angle) * heading.0);
+        let mut bullet =
+            commands.spawn(BulletBundle::new(heading, position).with_lifetime(settings.lifetime));
+        if settings.wrap {
+            bullet.insert(Wrapping);
+        }
+        if power_ups.is_active(PowerUp::Piercing) {
+            bullet.insert(Piercing::default());
+        }
     }
 }
 
-fn despawn_bullet(
-    mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform, &Position), With<Bullet>>,
+fn travel(
+    time: Res<Time>,
+    mut bullet_query: Query<(&mut Lifetime, Option<&mut Piercing>), With<Bullet>>,
 ) {
-    for (entity, transform, origin) in bullet_query.iter() {
-        let translation = transform.translation;
+    for (mut lifetime, piercing) in &mut bullet_query {
+        lifetime.0.tick(time.delta());
 
-        let distance = translation.distance(origin.0.extend(0.));
-        if distance > 1000. {
+        if let Some(mut piercing) = piercing {
+            piercing.0.tick(time.delta());
+        }
+    }
+}
+
+fn despawn_bullet(mut commands: Commands, bullet_query: Query<(Entity, &Lifetime), With<Bullet>>) {
+    for (entity, lifetime) in bullet_query.iter() {
+        if lifetime.0.finished() {
             commands.entity(entity).despawn_recursive();
         }
     }
@@ -110,23 +248,60 @@ fn despawn_bullet(
 
 fn detect_collisions(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<Bullet>>,
-    asteroid_query: Query<(Entity, &Transform), With<Asteroid>>,
+    arena: Res<Arena>,
+    grid: Res<SpatialGrid>,
+    bullet_query: Query<(Entity, &Transform, &Collider, Has<EnemyBullet>), With<Bullet>>,
+    mut piercing_query: Query<&mut Piercing>,
+    asteroid_query: Query<(&Transform, &Collider), With<Asteroid>>,
+    mut bullet_hit: EventWriter<BulletHitEvent>,
 ) {
-    for (bullet_entity, bullet_transform) in bullet_query.iter() {
-        let bullet_bounding_box =
-            Aabb2d::new(bullet_transform.translation.truncate(), Vec2::new(1., 1.));
-
-        for (entity, asteroid_transform) in asteroid_query.iter() {
-            let asteroid_bounding_box = Aabb2d::new(
-                asteroid_transform.translation.truncate(),
-                Vec2::new(20., 20.),
-            );
-
-            if bullet_bounding_box.intersects(&asteroid_bounding_box) {
-                commands.entity(entity).insert(Hit);
-                commands.entity(bullet_entity).despawn_recursive();
+    for (bullet_entity, bullet_transform, bullet_collider, enemy) in bullet_query.iter() {
+        let mut piercing = piercing_query.get_mut(bullet_entity).ok();
+        if piercing
+            .as_ref()
+            .is_some_and(|piercing| piercing.0.elapsed() < PIERCING_COOLDOWN)
+        {
+            continue;
+        }
+
+        let candidates = grid.candidates(
+            bullet_transform.translation.truncate(),
+            bullet_collider.bounding_radius(),
+        );
+
+        let mut hit = false;
+        for entity in candidates {
+            let Ok((asteroid_transform, asteroid_collider)) = asteroid_query.get(entity) else {
+                continue;
+            };
+
+            if bullet_collider.intersects_wrapped(
+                bullet_transform,
+                asteroid_collider,
+                asteroid_transform,
+                &arena,
+            ) {
+                commands
+                    .entity(entity)
+                    .try_insert(if enemy { Hit::Enemy } else { Hit::Player });
+
+                match piercing {
+                    Some(ref mut piercing) => piercing.0.reset(),
+                    None => commands.entity(bullet_entity).despawn_recursive(),
+                }
+                hit = true;
             }
         }
+
+        // a bullet counts once, however many asteroids it got at once
+        if hit && !enemy {
+            bullet_hit.send_default();
+        }
+    }
+}
+
+fn despawn_bullets(mut commands: Commands, bullet_query: Query<Entity, With<Bullet>>) {
+    for entity in &bullet_query {
+        commands.entity(entity).despawn_recursive();
     }
 }
@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+
+use crate::Arena;
+
+// Roughly the size of the largest asteroid
+const GRID_CELL_SIZE: f32 = 80.;
+
+pub struct CollisionPlugin;
+
+// Collisions are detected in FixedPostUpdate, once everything has moved for the tick. The broad
+// phase sorts every collider into a `SpatialGrid`, so the narrow phase only has to test the
+// colliders that are close to each other.
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialGrid>()
+            .configure_sets(
+                FixedPostUpdate,
+                (CollisionSet::BroadPhase, CollisionSet::NarrowPhase).chain(),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                update_spatial_grid.in_set(CollisionSet::BroadPhase),
+            );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CollisionSet {
+    BroadPhase,
+    NarrowPhase,
+}
+
+/// The shape an entity collides with, in its local space. It should match the shape that is drawn.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum Collider {
+    /// A circle of the given radius
+    Circle(f32),
+    /// An equilateral triangle pointing along local +Y, its corners the given radius away from the
+    /// center. Same as lyon's three sided `RegularPolygon`.
+    Triangle(f32),
+}
+
+impl Collider {
+    /// Radius of a circle around the center that contains the whole collider
+    pub fn bounding_radius(&self) -> f32 {
+        match *self {
+            Collider::Circle(radius) | Collider::Triangle(radius) => radius,
+        }
+    }
+
+    /// Narrow phase test between two colliders placed by their transforms. Scale is ignored.
+    pub fn intersects(
+        &self,
+        transform: &Transform,
+        other: &Collider,
+        other_transform: &Transform,
+    ) -> bool {
+        let center = transform.translation.truncate();
+        let other_center = other_transform.translation.truncate();
+
+        match (*self, *other) {
+            (Collider::Circle(radius), Collider::Circle(other_radius)) => {
+                center.distance_squared(other_center) <= (radius + other_radius).powi(2)
+            }
+            (Collider::Triangle(radius), Collider::Circle(other_radius)) => {
+                triangle_intersects_circle(triangle(radius, transform), other_center, other_radius)
+            }
+            (Collider::Circle(radius), Collider::Triangle(other_radius)) => {
+                triangle_intersects_circle(triangle(other_radius, other_transform), center, radius)
+            }
+            (Collider::Triangle(radius), Collider::Triangle(other_radius)) => triangles_intersect(
+                triangle(radius, transform),
+                triangle(other_radius, other_transform),
+            ),
+        }
+    }
+
+    /// Like [`Collider::intersects`], but tests against the copy of `other` on the far side of the
+    /// arena edges when that one is closer, so nothing passes through the seam untouched.
+    pub fn intersects_wrapped(
+        &self,
+        transform: &Transform,
+        other: &Collider,
+        other_transform: &Transform,
+        arena: &Arena,
+    ) -> bool {
+        let nearest = arena.nearest_image(
+            transform.translation.truncate(),
+            other_transform.translation.truncate(),
+        );
+        let other_transform =
+            other_transform.with_translation(nearest.extend(other_transform.translation.z));
+
+        self.intersects(transform, other, &other_transform)
+    }
+}
+
+fn triangle(radius: f32, transform: &Transform) -> [Vec2; 3] {
+    [90_f32, 210., 330.].map(|angle| {
+        let corner = Vec2::from_angle(angle.to_radians()) * radius;
+        (transform.translation + transform.rotation * corner.extend(0.)).truncate()
+    })
+}
+
+fn triangle_intersects_circle(triangle: [Vec2; 3], center: Vec2, radius: f32) -> bool {
+    contains(triangle, center)
+        || edges(triangle)
+            .iter()
+            .any(|&(start, end)| distance_to_segment(center, start, end) <= radius)
+}
+
+fn triangles_intersect(a: [Vec2; 3], b: [Vec2; 3]) -> bool {
+    a.iter().any(|&corner| contains(b, corner))
+        || b.iter().any(|&corner| contains(a, corner))
+        || edges(a).iter().any(|&edge| {
+            edges(b)
+                .iter()
+                .any(|&other_edge| segments_cross(edge, other_edge))
+        })
+}
+
+fn edges([a, b, c]: [Vec2; 3]) -> [(Vec2, Vec2); 3] {
+    [(a, b), (b, c), (c, a)]
+}
+
+fn contains([a, b, c]: [Vec2; 3], point: Vec2) -> bool {
+    let sides = [
+        (b - a).perp_dot(point - a),
+        (c - b).perp_dot(point - b),
+        (a - c).perp_dot(point - c),
+    ];
+
+    sides.iter().all(|&side| side >= 0.) || sides.iter().all(|&side| side <= 0.)
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t = ((point - start).dot(segment) / segment.length_squared()).clamp(0., 1.);
+
+    point.distance(start + segment * t)
+}
+
+fn segments_cross((a, b): (Vec2, Vec2), (c, d): (Vec2, Vec2)) -> bool {
+    let ab = b - a;
+    let cd = d - c;
+
+    (ab.perp_dot(c - a) * ab.perp_dot(d - a) < 0.) && (cd.perp_dot(a - c) * cd.perp_dot(b - c) < 0.)
+}
+
+/// A uniform grid over the arena that buckets colliders by their center.
+///
+/// The grid wraps around at the arena edges like the playfield does, so colliders just across an
+/// edge are candidates too. The arena is divided into whole cells, which keeps the wrapped cells
+/// lined up with the ones on the opposite edge.
+#[derive(Resource, Debug)]
+pub struct SpatialGrid {
+    origin: Vec2,
+    cell_size: Vec2,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<Entity>>,
+    max_radius: f32,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(&Arena::default(), GRID_CELL_SIZE)
+    }
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid whose cells are at least `cell_size` wide and high
+    pub fn new(arena: &Arena, cell_size: f32) -> Self {
+        let mut grid = Self {
+            origin: Vec2::ZERO,
+            cell_size: Vec2::ONE,
+            columns: 0,
+            rows: 0,
+            cells: Vec::new(),
+            max_radius: 0.,
+        };
+        grid.reset(arena, cell_size);
+        grid
+    }
+
+    /// Empties the grid and fits it to the arena, keeping the allocated cells where possible
+    pub fn reset(&mut self, arena: &Arena, cell_size: f32) {
+        let size = Vec2::new(arena.width, arena.height);
+        let columns = (size.x / cell_size).floor().max(1.) as usize;
+        let rows = (size.y / cell_size).floor().max(1.) as usize;
+
+        self.origin = -size / 2.;
+        self.cell_size = size / Vec2::new(columns as f32, rows as f32);
+        self.columns = columns;
+        self.rows = rows;
+        self.cells.resize_with(columns * rows, Vec::new);
+        self.cells.truncate(columns * rows);
+        self.cells.iter_mut().for_each(Vec::clear);
+        self.max_radius = 0.;
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2, radius: f32) {
+        let cell = self.cell(position);
+        let index = self.index(cell.x, cell.y);
+
+        self.cells[index].push(entity);
+        self.max_radius = self.max_radius.max(radius);
+    }
+
+    /// Entities whose colliders may overlap a circle of `radius` around `position`.
+    /// Every entity is returned at most once.
+    pub fn candidates(&self, position: Vec2, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        let reach = Vec2::splat(radius + self.max_radius);
+        let min = self.cell(position - reach);
+        let max = self.cell(position + reach);
+
+        // never visit a cell twice, even when the reach is wider than the arena
+        let columns = ((max.x - min.x + 1) as usize).min(self.columns);
+        let rows = ((max.y - min.y + 1) as usize).min(self.rows);
+
+        (0..rows).flat_map(move |row| {
+            (0..columns).flat_map(move |column| {
+                let index = self.index(min.x + column as i32, min.y + row as i32);
+                self.cells[index].iter().copied()
+            })
+        })
+    }
+
+    // Unwrapped cell coordinates, may lie outside the grid
+    fn cell(&self, position: Vec2) -> IVec2 {
+        ((position - self.origin) / self.cell_size)
+            .floor()
+            .as_ivec2()
+    }
+
+    fn index(&self, column: i32, row: i32) -> usize {
+        let column = column.rem_euclid(self.columns as i32) as usize;
+        let row = row.rem_euclid(self.rows as i32) as usize;
+
+        row * self.columns + column
+    }
+}
+
+fn update_spatial_grid(
+    arena: Res<Arena>,
+    mut grid: ResMut<SpatialGrid>,
+    collider_query: Query<(Entity, &Transform, &Collider)>,
+) {
+    grid.reset(&arena, GRID_CELL_SIZE);
+
+    for (entity, transform, collider) in &collider_query {
+        grid.insert(
+            entity,
+            transform.translation.truncate(),
+            collider.bounding_radius(),
+        );
+    }
+}
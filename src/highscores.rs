@@ -0,0 +1,202 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{player::Player, waves::WaveReached, GameState};
+
+const HIGH_SCORE_COUNT: usize = 10;
+const INITIALS_LENGTH: usize = 3;
+
+pub struct HighScoresPlugin {
+    pub path: Option<PathBuf>,
+}
+
+// The best scores are kept in a file between runs. When a game ends with a score good enough for
+// the table, the game over screen asks for the player's initials before it is added.
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        let high_scores = match &self.path {
+            Some(path) => HighScores::load(path).unwrap_or_else(|error| {
+                if error.kind() != io::ErrorKind::NotFound {
+                    warn!("Could not load high scores {}: {}", path.display(), error);
+                }
+                HighScores::default()
+            }),
+            None => HighScores::default(),
+        };
+
+        app.insert_resource(high_scores)
+            .insert_resource(HighScoresFile(self.path.clone()))
+            .add_systems(OnEnter(GameState::GameOver), start_initials_entry)
+            .add_systems(Update, enter_initials)
+            .add_systems(OnExit(GameState::GameOver), finish_initials_entry);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: i32,
+    pub wave: u32,
+    /// The day the score was set, as `YYYY-MM-DD`
+    pub date: String,
+}
+
+/// The best scores so far, best first
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Where the high scores are kept unless told otherwise, in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("asteroids").join("high_scores.ron"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(self, default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, contents)
+    }
+
+    /// Whether the score makes it into the table
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0
+            && (self.entries.len() < HIGH_SCORE_COUNT
+                || self
+                    .entries
+                    .last()
+                    .is_some_and(|lowest| score > lowest.score))
+    }
+
+    /// Adds the entry below any equal scores, dropping whatever falls off the end
+    pub fn insert(&mut self, entry: HighScore) {
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORE_COUNT);
+    }
+}
+
+#[derive(Resource)]
+struct HighScoresFile(Option<PathBuf>);
+
+/// A qualifying score waiting for the player to type their initials
+#[derive(Resource, Debug)]
+pub(crate) struct InitialsEntry {
+    pub initials: String,
+    score: i32,
+    wave: u32,
+}
+
+fn start_initials_entry(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    wave: Res<WaveReached>,
+    player_query: Query<&Player>,
+) {
+    if let Ok(player) = player_query.get_single() {
+        if high_scores.qualifies(player.score) {
+            commands.insert_resource(InitialsEntry {
+                initials: String::new(),
+                score: player.score,
+                wave: wave.0,
+            });
+        }
+    }
+}
+
+fn enter_initials(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    entry: Option<ResMut<InitialsEntry>>,
+    mut high_scores: ResMut<HighScores>,
+    file: Res<HighScoresFile>,
+) {
+    // keys are read all the time, so the ones pressed while playing don't end up in the initials
+    let Some(mut entry) = entry else {
+        keyboard_events.clear();
+        return;
+    };
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(characters) => {
+                for letter in characters.chars().filter(char::is_ascii_alphabetic) {
+                    if entry.initials.len() < INITIALS_LENGTH {
+                        entry.initials.push(letter.to_ascii_uppercase());
+                    }
+                }
+            }
+            Key::Backspace => {
+                entry.initials.pop();
+            }
+            Key::Enter if entry.initials.len() == INITIALS_LENGTH => {
+                record(&entry, &mut high_scores, &file);
+                commands.remove_resource::<InitialsEntry>();
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+// Leaving the game over screen before confirming still keeps the score
+fn finish_initials_entry(
+    mut commands: Commands,
+    entry: Option<Res<InitialsEntry>>,
+    mut high_scores: ResMut<HighScores>,
+    file: Res<HighScoresFile>,
+) {
+    if let Some(entry) = entry {
+        record(&entry, &mut high_scores, &file);
+        commands.remove_resource::<InitialsEntry>();
+    }
+}
+
+fn record(entry: &InitialsEntry, high_scores: &mut HighScores, file: &HighScoresFile) {
+    let mut initials = entry.initials.clone();
+    while initials.len() < INITIALS_LENGTH {
+        initials.push('-');
+    }
+
+    high_scores.insert(HighScore {
+        initials,
+        score: entry.score,
+        wave: entry.wave,
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+    });
+
+    if let Some(path) = &file.0 {
+        if let Err(error) = high_scores.save(path) {
+            error!("Could not save high scores {}: {}", path.display(), error);
+        }
+    }
+}
@@ -1,115 +1,322 @@
+use std::{f32::consts::PI, ops::Range, path::PathBuf};
+
 use asteroids::AsteroidsPlugin;
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ExecutorKind, input::InputPlugin, prelude::*, state::app::StatesPlugin};
 use bevy_prototype_lyon::prelude::*;
 use menu::MenuPlugin;
+use pause::PausePlugin;
+pub use pause::PauseState;
 use player::PlayerPlugin;
-use rand::{prelude::thread_rng, Rng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use actions::ActionsPlugin;
+pub use actions::{ActiveGamepad, GameControl, GamepadConfig, KeyBindings};
+pub use asteroids::{Asteroid, AsteroidBundle, AsteroidSize};
 use bullets::BulletsPlugin;
+pub use bullets::{Bullet, BulletBundle, BulletSettings, EnemyBullet, FireCooldown, Lifetime};
+use collision::CollisionPlugin;
+pub use collision::{Collider, CollisionSet, SpatialGrid};
+use highscores::HighScoresPlugin;
+pub use highscores::{HighScore, HighScores};
+pub use player::Player;
+use powerups::PowerUpsPlugin;
+pub use powerups::{ActivePowerUps, PickupBundle, PowerUp};
+pub use replay::Replay;
+use replay::{Playback, ReplayMode, ReplayPlugin};
+use rng::RngPlugin;
+pub use rng::{GameRng, GameSeed};
+use saucer::SaucerPlugin;
+pub use saucer::{Saucer, SaucerBundle, SaucerSize};
+pub use settings::Settings;
+use settings::SettingsPlugin;
+pub use shield::Shield;
+use shield::ShieldPlugin;
 use ship::ShipPlugin;
+pub use ship::{HyperspaceSettings, Ship, ShipState, Thrusting};
 use sounds::SoundPlugin;
+pub use sounds::{SoundChannel, VolumeMixer};
 use ui::UiPlugin;
+use waves::WavesPlugin;
+pub use waves::{DifficultyCurve, Heartbeat, Ramp, Wave, WaveReached};
 
 mod actions;
 mod asteroids;
 mod bullets;
+mod collision;
+mod highscores;
 mod menu;
+mod pause;
 mod player;
+mod powerups;
+mod replay;
+mod rng;
+mod saucer;
+mod settings;
+mod shield;
 mod ship;
 mod sounds;
 mod ui;
+mod waves;
 
 pub const TEXT_SIZE: f32 = 32.;
 pub const TEXT_COLOR: Color = Color::WHITE;
 
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
-enum GameState {
+pub enum GameState {
     #[default]
     Menu,
     Playing,
+    /// The game is over, the playfield stays frozen behind the final score until the player moves on
+    GameOver,
+}
+
+/// The game plugin.
+///
+/// `Asteroids::default()` expects `DefaultPlugins` and a window. `Asteroids::headless(arena)`
+/// runs the same gameplay on top of `MinimalPlugins`: no camera, rendering, UI or audio, and the
+/// playfield size comes from the given [`Arena`] instead of the window.
+///
+/// Gameplay randomness is seeded from [`Asteroids::with_seed`], or from a fresh random seed for
+/// every game if none is given. Games can be recorded to a file with [`Asteroids::recording`] and
+/// played back with [`Asteroids::replaying`]. How hard the waves get is set with
+/// [`Asteroids::with_difficulty`]. The high score table and the [`Settings`] are kept in memory
+/// only, unless files are given with [`Asteroids::with_high_scores`] and
+/// [`Asteroids::with_settings`].
+#[derive(Default)]
+pub struct Asteroids {
+    headless: Option<Arena>,
+    seed: Option<u64>,
+    replay: Option<ReplayMode>,
+    difficulty: DifficultyCurve,
+    high_scores: Option<PathBuf>,
+    settings: Option<PathBuf>,
 }
 
-pub struct Asteroids;
+impl Asteroids {
+    pub fn headless(arena: Arena) -> Self {
+        Self {
+            headless: Some(arena),
+            ..default()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn recording(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay = Some(ReplayMode::Record(path.into()));
+        self
+    }
+
+    pub fn with_difficulty(mut self, difficulty: DifficultyCurve) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn with_high_scores(mut self, path: impl Into<PathBuf>) -> Self {
+        self.high_scores = Some(path.into());
+        self
+    }
+
+    pub fn with_settings(mut self, path: impl Into<PathBuf>) -> Self {
+        self.settings = Some(path.into());
+        self
+    }
+
+    /// Plays the replay back instead of reading the keyboard, starting with the replay's seed
+    pub fn replaying(mut self, replay: Replay) -> Self {
+        self.seed = Some(replay.seed);
+        self.replay = Some(ReplayMode::Playback(replay));
+        self
+    }
+}
 
 impl Plugin for Asteroids {
     fn build(&self, app: &mut App) {
+        match self.headless {
+            Some(arena) => {
+                // MinimalPlugins brings neither states nor input
+                if !app.is_plugin_added::<StatesPlugin>() {
+                    app.add_plugins(StatesPlugin);
+                }
+                if !app.is_plugin_added::<InputPlugin>() {
+                    app.add_plugins(InputPlugin);
+                }
+
+                app.insert_resource(arena);
+            }
+            None => {
+                app.init_resource::<Arena>()
+                    .add_plugins((UiPlugin, MenuPlugin, SoundPlugin, ShapePlugin))
+                    .add_systems(Startup, spawn_camera)
+                    // a replay brings the arena it was recorded in along
+                    .add_systems(
+                        PreUpdate,
+                        fit_arena_to_window.run_if(not(resource_exists::<Playback>)),
+                    );
+            }
+        }
+
         app.init_state::<GameState>()
             .add_plugins((
+                RngPlugin { seed: self.seed },
+                CollisionPlugin,
                 ActionsPlugin,
                 ShipPlugin,
+                ShieldPlugin,
                 BulletsPlugin,
                 AsteroidsPlugin,
+                SaucerPlugin,
+                PowerUpsPlugin,
                 PlayerPlugin,
-                UiPlugin,
-                MenuPlugin,
-                SoundPlugin,
-                ShapePlugin,
+                WavesPlugin {
+                    difficulty: self.difficulty.clone(),
+                },
+                HighScoresPlugin {
+                    path: self.high_scores.clone(),
+                },
+                PausePlugin,
+                SettingsPlugin {
+                    path: self.settings.clone(),
+                },
             ))
-            .add_systems(Startup, spawn_camera)
-            .add_systems(FixedUpdate, wrap);
+            .add_systems(
+                FixedUpdate,
+                (displace, wrap)
+                    .chain()
+                    .in_set(Kinematics)
+                    .run_if(not(in_state(GameState::GameOver))),
+            )
+            .add_systems(FixedLast, apply_state_transitions);
+
+        // Gameplay runs in FixedUpdate and FixedPostUpdate. Running it on a single thread and
+        // applying state transitions at the end of every tick keeps it deterministic, so replays
+        // play out the same way no matter the frame rate they were recorded at. Bevy only applies
+        // them once per frame on its own, so a frame that runs several ticks would otherwise keep
+        // the old state for the rest of them.
+        app.edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        })
+        .edit_schedule(FixedPostUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
+        if let Some(mode) = &self.replay {
+            app.add_plugins(ReplayPlugin { mode: mode.clone() });
+        }
     }
 }
 
+fn apply_state_transitions(world: &mut World) {
+    world.run_schedule(StateTransition);
+}
+
 fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
+/// The logical size of the playfield, centered on the origin.
+///
+/// Follows the window size when there is one, otherwise it is fixed by [`Asteroids::headless`].
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Arena {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    fn half_extents(&self) -> Vec2 {
+        self.size() / 2.
+    }
+
+    /// Wraps a position back into the arena. The arena is a torus: leaving it over one edge enters
+    /// it from the opposite edge, at the same coordinate along that edge.
+    pub fn wrap(&self, position: Vec2) -> Vec2 {
+        (position + self.half_extents()).rem_euclid(self.size()) - self.half_extents()
+    }
+
+    /// The copy of `to` on the torus that is closest to `from`, which may lie outside the arena
+    pub fn nearest_image(&self, from: Vec2, to: Vec2) -> Vec2 {
+        from + self.wrap(to - from)
+    }
+}
+
+impl Default for Arena {
+    // Same as bevy's default window resolution
+    fn default() -> Self {
+        Self::new(1280., 720.)
+    }
+}
+
+fn fit_arena_to_window(window: Query<&Window>, mut arena: ResMut<Arena>) {
+    if let Ok(window) = window.get_single() {
+        arena.set_if_neq(Arena::new(window.width(), window.height()));
+    }
+}
+
 #[derive(Component, Debug, Clone, Copy, Default)]
-struct Heading(Vec3);
+pub struct Heading(pub Vec3);
 
 #[derive(Component, Debug, Clone, Copy, Default)]
-struct Position(Vec2);
+pub struct Position(pub Vec2);
 
 #[derive(Component)]
 struct Wrapping;
 
-// This vector gives the direction and velocity the entity is travelling in
+// This vector gives the direction and velocity the entity is travelling in, in units per second
 #[derive(Component, Debug, Clone, Copy)]
-struct Velocity(Vec3);
+pub struct Velocity(pub Vec3);
 
 impl Velocity {
-    fn random() -> Self {
-        Self::random_with_speed(thread_rng().gen_range(0.1..3.))
+    fn random(speed: Range<f32>, rng: &mut impl Rng) -> Self {
+        let speed = rng.gen_range(speed);
+        Self::random_with_speed(speed, rng)
     }
 
-    fn random_with_speed(speed: f32) -> Self {
-        Self(
-            Vec3::new(
-                thread_rng().gen_range(-1.0..1.0),
-                thread_rng().gen_range(-1.0..1.0),
-                0.,
-            ) * speed,
-        )
+    // Moves in a random direction at exactly the given speed
+    fn random_with_speed(speed: f32, rng: &mut impl Rng) -> Self {
+        Self(Vec2::from_angle(rng.gen_range(-PI..PI)).extend(0.) * speed)
     }
 }
 
-#[derive(Component)]
-struct Collider;
+/// Marks an entity that was hit during this tick, and who hit it
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum Hit {
+    /// By the ship or its bullets, which scores
+    Player,
+    /// By anything else
+    Enemy,
+}
 
-#[derive(Component, Clone)]
-struct Hit;
+/// Everything that moves is moved in this set, by [`Velocity`] scaled with the fixed timestep, and
+/// then wrapped around the arena. Systems that steer something run before it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Kinematics;
 
-// Todo: This should be extracted, the asteroids will need the same logic
-fn wrap(window: Query<&Window>, mut wrapping_query: Query<&mut Transform, With<Wrapping>>) {
-    if let Ok(window) = window.get_single() {
-        let (width, height) = (window.width(), window.height());
+fn displace(time: Res<Time>, mut moving_query: Query<(&mut Transform, &Velocity)>) {
+    for (mut transform, velocity) in &mut moving_query {
+        transform.translation += velocity.0 * time.delta_seconds();
+    }
+}
 
-        for mut transform in &mut wrapping_query {
-            let position = transform.translation.truncate();
+fn wrap(arena: Res<Arena>, mut wrapping_query: Query<&mut Transform, With<Wrapping>>) {
+    for mut transform in &mut wrapping_query {
+        let position = transform.translation.truncate();
+        let wrapped = arena.wrap(position);
 
-            if position.x > width / 2. {
-                transform.translation = Vec3::new(position.x - width, position.y * -1., 0.)
-            }
-            if position.x < width / -2. {
-                transform.translation = Vec3::new(position.x + width, position.y * -1., 0.)
-            }
-            if position.y > height / 2. {
-                transform.translation = Vec3::new(position.x * -1., position.y - height, 0.);
-            }
-            if position.y < height / -2. {
-                transform.translation = Vec3::new(position.x * -1., position.y + height, 0.);
-            }
+        if wrapped != position {
+            transform.translation = wrapped.extend(transform.translation.z);
         }
     }
 }
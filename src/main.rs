@@ -1,10 +1,62 @@
-use bevy::prelude::*;
+use std::{io, path::PathBuf};
 
-use asteroids::Asteroids;
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
 
+use asteroids::{Asteroids, DifficultyCurve, HighScores, Replay, Settings};
+
+// Looked for where Bevy looks for the sounds, not relative to the working directory
+const DIFFICULTY_FILE: &str = "assets/difficulty.ron";
+
+// Usage: asteroids [--seed <seed>] [--record <file> | --replay <file>] [--difficulty <file>]
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugins(Asteroids)
-        .run();
+    // the log is set up along with the default plugins, so they come first
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
+
+    let mut asteroids = Asteroids::default();
+    let mut args = std::env::args().skip(1);
+    let mut difficulty = None;
+
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--seed", Some(seed)) => {
+                asteroids = asteroids.with_seed(seed.parse().expect("seed must be a number"))
+            }
+            ("--record", Some(path)) => asteroids = asteroids.recording(path),
+            ("--replay", Some(path)) => {
+                let replay = Replay::load(&path)
+                    .unwrap_or_else(|error| panic!("Could not load replay {}: {}", path, error));
+                asteroids = asteroids.replaying(replay)
+            }
+            ("--difficulty", Some(path)) => difficulty = Some(path),
+            _ => panic!("Unknown argument {}", arg),
+        }
+    }
+
+    let required = difficulty.is_some();
+    let path = difficulty
+        .map(PathBuf::from)
+        .unwrap_or_else(|| FileAssetReader::get_base_path().join(DIFFICULTY_FILE));
+    match DifficultyCurve::load(&path) {
+        Ok(curve) => asteroids = asteroids.with_difficulty(curve),
+        // the difficulty file that comes with the game is optional, one asked for is not
+        Err(error) if error.kind() == io::ErrorKind::NotFound && !required => warn!(
+            "No difficulty curve at {}, playing with the default one",
+            path.display()
+        ),
+        Err(error) => panic!(
+            "Could not load difficulty curve {}: {}",
+            path.display(),
+            error
+        ),
+    }
+
+    if let Some(path) = HighScores::default_path() {
+        asteroids = asteroids.with_high_scores(path);
+    }
+    if let Some(path) = Settings::default_path() {
+        asteroids = asteroids.with_settings(path);
+    }
+
+    app.add_plugins(asteroids).run();
 }
@@ -1,43 +1,75 @@
-use bevy::prelude::*;
+use bevy::{input::InputSystem, prelude::*};
 
-use crate::{GameState, TEXT_COLOR};
+use crate::{
+    actions::{GameControl, GamepadConfig, KeyBindings},
+    highscores::{HighScores, InitialsEntry},
+    pause::{PauseState, Restart},
+    player::Player,
+    sounds::{SoundChannel, VolumeMixer},
+    waves::WaveReached,
+    BulletSettings, GameState, TEXT_COLOR, TEXT_SIZE,
+};
 
 pub struct MenuPlugin;
 
+// The main menu with the high score table, the game over sign shown over the frozen playfield
+// once the last life is lost, and the pause menu. The main and the pause menu both lead to the
+// settings page, where the next key pressed after picking a control is bound to it.
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Menu), spawn_menu)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_sign)
+            .add_systems(OnEnter(PauseState::Paused), spawn_pause_menu)
+            .add_systems(Update, handle_menu_buttons)
             .add_systems(
                 Update,
-                (handle_menu_buttons).run_if(in_state(GameState::Menu)),
+                update_initials_text.run_if(in_state(GameState::GameOver)),
             )
-            .add_systems(OnExit(GameState::Menu), despawn_menu);
+            .add_systems(
+                PreUpdate,
+                capture_key
+                    .after(InputSystem)
+                    .run_if(resource_exists::<Rebinding>),
+            )
+            .add_systems(Update, update_settings_text)
+            .add_systems(OnExit(GameState::Menu), (despawn_menu, stop_rebinding))
+            .add_systems(OnExit(GameState::GameOver), despawn_menu)
+            .add_systems(OnExit(PauseState::Paused), (despawn_menu, stop_rebinding));
     }
 }
 
 #[derive(Component, Debug)]
 struct Menu;
 
-#[derive(Component, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
 enum ButtonAction {
     StartGame,
+    PlayAgain,
+    MainMenu,
+    Resume,
+    Restart,
+    Settings,
+    Back,
+    ToggleBulletWrap,
+    CycleDeadZone,
+    CycleVolume(SoundChannel),
+    ToggleMute,
+    ToggleDistanceAttenuation,
+    Rebind(GameControl),
 }
 
-fn spawn_menu(mut commands: Commands) {
-    let button_style = Style {
-        width: Val::Px(300.0),
-        height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
-        justify_content: JustifyContent::Center,
-        align_items: AlignItems::Center,
-        ..default()
-    };
-    let button_text_style = TextStyle {
-        font_size: 40.0,
-        color: TEXT_COLOR,
-        ..default()
-    };
+// Any of the screens below. Only ever one of them is up, apart from the settings page hiding behind
+// the main or the pause menu.
+#[derive(Component)]
+struct Screen;
 
+// Covers the whole window and stacks whatever `children` spawns in a column in the middle of it
+fn spawn_panel(
+    commands: &mut Commands,
+    marker: impl Component,
+    background: Color,
+    children: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
     commands
         .spawn((
             NodeBundle {
@@ -50,7 +82,8 @@ fn spawn_menu(mut commands: Commands) {
                 },
                 ..default()
             },
-            Menu,
+            Screen,
+            marker,
         ))
         .with_children(|parent| {
             parent
@@ -62,53 +95,383 @@ fn spawn_menu(mut commands: Commands) {
                         padding: UiRect::all(Val::Px(10.)),
                         ..default()
                     },
+                    background_color: background.into(),
                     ..default()
                 })
-                .with_children(|parent| {
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                ..default()
-                            },
-                            ButtonAction::StartGame,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Start Game",
-                                button_text_style.clone(),
-                            ));
-                        });
-                });
+                .with_children(children);
+        })
+        .id()
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, action: ButtonAction) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    height: Val::Px(65.0),
+                    margin: UiRect::all(Val::Px(20.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 40.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ));
         });
 }
 
+fn spawn_menu(mut commands: Commands, high_scores: Res<HighScores>) {
+    let table_style = TextStyle {
+        font_size: TEXT_SIZE * 0.75,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    spawn_panel(&mut commands, Menu, Color::NONE, |parent| {
+        spawn_button(parent, "Start Game", ButtonAction::StartGame);
+        spawn_button(parent, "Settings", ButtonAction::Settings);
+
+        if !high_scores.entries.is_empty() {
+            parent.spawn(TextBundle::from_section("High Scores", table_style.clone()));
+        }
+        for (rank, entry) in high_scores.entries.iter().enumerate() {
+            parent.spawn(TextBundle::from_section(
+                format!(
+                    "{:>2}. {} {:>7}  wave {:>2}  {}",
+                    rank + 1,
+                    entry.initials,
+                    entry.score,
+                    entry.wave,
+                    entry.date
+                ),
+                table_style.clone(),
+            ));
+        }
+    });
+    spawn_settings_menu(&mut commands);
+}
+
+const OVERLAY_COLOR: Color = Color::srgba(0., 0., 0., 0.8);
+
 #[derive(Component, Debug)]
 struct GameOverSign;
 
-fn despawn_menu(
+// Asks for the initials while a new high score waits for them
+#[derive(Component)]
+struct InitialsText;
+
+fn spawn_game_over_sign(
     mut commands: Commands,
-    menu_query: Query<Entity, With<Menu>>,
-    game_over_sign_query: Query<Entity, With<GameOverSign>>,
+    wave: Res<WaveReached>,
+    player_query: Query<&Player>,
 ) {
-    if let Ok(menu) = menu_query.get_single() {
-        commands.entity(menu).despawn_recursive();
-    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let accuracy = match player.accuracy() {
+        Some(accuracy) => format!("{:.0}%", accuracy * 100.),
+        None => "-".to_string(),
+    };
+    let text_style = TextStyle {
+        font_size: 40.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    spawn_panel(&mut commands, GameOverSign, OVERLAY_COLOR, |parent| {
+        parent.spawn(TextBundle::from_section(
+            "Game Over",
+            TextStyle {
+                font_size: 80.0,
+                ..text_style.clone()
+            },
+        ));
+        for line in [
+            format!("Score {}", player.score),
+            format!("Wave {}", wave.0),
+            format!("Accuracy {}", accuracy),
+        ] {
+            parent.spawn(TextBundle::from_section(line, text_style.clone()));
+        }
+        parent.spawn((
+            TextBundle::from_section("", text_style.clone()),
+            InitialsText,
+        ));
+        spawn_button(parent, "Play again", ButtonAction::PlayAgain);
+        spawn_button(parent, "Main menu", ButtonAction::MainMenu);
+    });
+}
+
+#[derive(Component, Debug)]
+struct PauseMenu;
+
+#[derive(Component, Debug)]
+struct SettingsMenu;
+
+// The text of a settings button, which shows the setting's current value
+#[derive(Component)]
+struct SettingText(ButtonAction);
+
+/// The control the next key pressed is bound to
+#[derive(Resource)]
+struct Rebinding(GameControl);
+
+fn spawn_pause_menu(mut commands: Commands) {
+    spawn_panel(&mut commands, PauseMenu, OVERLAY_COLOR, |parent| {
+        parent.spawn(TextBundle::from_section(
+            "Paused",
+            TextStyle {
+                font_size: 80.0,
+                color: TEXT_COLOR,
+                ..default()
+            },
+        ));
+        spawn_button(parent, "Resume", ButtonAction::Resume);
+        spawn_button(parent, "Restart", ButtonAction::Restart);
+        spawn_button(parent, "Settings", ButtonAction::Settings);
+        spawn_button(parent, "Quit to Menu", ButtonAction::MainMenu);
+    });
+    spawn_settings_menu(&mut commands);
+}
 
-    if let Ok(game_over_sign) = game_over_sign_query.get_single() {
-        commands.entity(game_over_sign).despawn_recursive();
+// Spawned hidden, the menu it belongs to and the settings page take turns being shown
+fn spawn_settings_menu(commands: &mut Commands) {
+    let settings = spawn_panel(commands, SettingsMenu, OVERLAY_COLOR, |parent| {
+        spawn_setting(parent, ButtonAction::ToggleBulletWrap);
+        spawn_setting(parent, ButtonAction::CycleDeadZone);
+        for channel in SoundChannel::ALL {
+            spawn_setting(parent, ButtonAction::CycleVolume(channel));
+        }
+        spawn_setting(parent, ButtonAction::ToggleMute);
+        spawn_setting(parent, ButtonAction::ToggleDistanceAttenuation);
+        for control in GameControl::ALL {
+            spawn_setting(parent, ButtonAction::Rebind(control));
+        }
+        spawn_button(parent, "Back", ButtonAction::Back);
+    });
+    commands.entity(settings).insert(Visibility::Hidden);
+}
+
+fn spawn_setting(parent: &mut ChildBuilder, action: ButtonAction) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(500.0),
+                    height: Val::Px(40.0),
+                    margin: UiRect::all(Val::Px(5.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: TEXT_SIZE * 0.75,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                SettingText(action),
+            ));
+        });
+}
+
+fn despawn_menu(mut commands: Commands, screen_query: Query<Entity, With<Screen>>) {
+    for entity in &screen_query {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn handle_menu_buttons(
-    interaction_query: Query<(&Interaction, &ButtonAction), With<Button>>,
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &ButtonAction), (Changed<Interaction>, With<Button>)>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut bullet_settings: ResMut<BulletSettings>,
+    mut gamepad_config: ResMut<GamepadConfig>,
+    mut mixer: ResMut<VolumeMixer>,
+    mut menu_query: Query<&mut Visibility, (With<Screen>, Without<SettingsMenu>)>,
+    mut settings_menu_query: Query<&mut Visibility, With<SettingsMenu>>,
 ) {
+    let mut show_settings = |show: bool| {
+        let (shown, hidden) = if show {
+            (Visibility::Inherited, Visibility::Hidden)
+        } else {
+            (Visibility::Hidden, Visibility::Inherited)
+        };
+        for mut visibility in &mut settings_menu_query {
+            *visibility = shown;
+        }
+        for mut visibility in &mut menu_query {
+            *visibility = hidden;
+        }
+    };
+
     for (interaction, button_action) in interaction_query.iter() {
         if *interaction == Interaction::Pressed {
             match button_action {
-                ButtonAction::StartGame => next_state.set(GameState::Playing),
+                ButtonAction::StartGame | ButtonAction::PlayAgain => {
+                    next_state.set(GameState::Playing)
+                }
+                ButtonAction::MainMenu => next_state.set(GameState::Menu),
+                ButtonAction::Resume => next_pause_state.set(PauseState::Running),
+                // going through the menu clears the playfield, a new game starts from there
+                ButtonAction::Restart => {
+                    commands.insert_resource(Restart);
+                    next_state.set(GameState::Menu);
+                }
+                ButtonAction::Settings => show_settings(true),
+                ButtonAction::Back => {
+                    commands.remove_resource::<Rebinding>();
+                    show_settings(false);
+                }
+                ButtonAction::ToggleBulletWrap => bullet_settings.wrap = !bullet_settings.wrap,
+                ButtonAction::CycleDeadZone => {
+                    gamepad_config.dead_zone = next_dead_zone(gamepad_config.dead_zone)
+                }
+                ButtonAction::CycleVolume(channel) => {
+                    let level = next_volume(mixer.level(*channel));
+                    mixer.set_level(*channel, level);
+                }
+                ButtonAction::ToggleMute => mixer.muted = !mixer.muted,
+                ButtonAction::ToggleDistanceAttenuation => {
+                    mixer.distance_attenuation = !mixer.distance_attenuation
+                }
+                ButtonAction::Rebind(control) => commands.insert_resource(Rebinding(*control)),
             }
         }
     }
 }
+
+// Runs right after the keyboard is read and takes the key back out of it, so it doesn't also
+// pause the game or fire before the bindings are in place
+fn capture_key(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    rebinding: Res<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+) {
+    let Some(&key) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+
+    keyboard_input.reset(key);
+    // Escape backs out of rebinding, unless it is what the pause key is rebound to
+    if key != KeyCode::Escape || rebinding.0 == GameControl::Pause {
+        // a key that can't be bound without leaving another control keyless waits for another
+        if !bindings.bind(rebinding.0, key) {
+            return;
+        }
+    }
+    commands.remove_resource::<Rebinding>();
+}
+
+fn stop_rebinding(mut commands: Commands) {
+    commands.remove_resource::<Rebinding>();
+}
+
+fn update_initials_text(
+    entry: Option<Res<InitialsEntry>>,
+    mut text_query: Query<&mut Text, With<InitialsText>>,
+) {
+    for mut text in &mut text_query {
+        text.sections[0].value = match &entry {
+            Some(entry) => format!("New high score! Your initials: {:_<3}", entry.initials),
+            None => String::new(),
+        };
+    }
+}
+
+// Steps through 5% to 30% and around again
+fn next_dead_zone(dead_zone: f32) -> f32 {
+    let step = ((dead_zone * 20.).round() as u32 % 6) + 1;
+    step as f32 / 20.
+}
+
+// Steps through 0% to 100% in tens and around again
+fn next_volume(level: f32) -> f32 {
+    let step = ((level * 10.).round() as u32 + 1) % 11;
+    step as f32 / 10.
+}
+
+fn update_settings_text(
+    bullet_settings: Res<BulletSettings>,
+    gamepad_config: Res<GamepadConfig>,
+    mixer: Res<VolumeMixer>,
+    bindings: Res<KeyBindings>,
+    rebinding: Option<Res<Rebinding>>,
+    mut text_query: Query<(&mut Text, &SettingText)>,
+) {
+    for (mut text, setting) in &mut text_query {
+        text.sections[0].value = match setting.0 {
+            ButtonAction::ToggleBulletWrap => format!(
+                "Wrapping bullets: {}",
+                if bullet_settings.wrap { "On" } else { "Off" }
+            ),
+            ButtonAction::CycleDeadZone => {
+                format!("Stick dead zone: {:.0}%", gamepad_config.dead_zone * 100.)
+            }
+            ButtonAction::CycleVolume(channel) => {
+                format!("{}: {:.0}%", channel.label(), mixer.level(channel) * 100.)
+            }
+            ButtonAction::ToggleMute => {
+                format!("Sound: {}", if mixer.muted { "Muted" } else { "On" })
+            }
+            ButtonAction::ToggleDistanceAttenuation => format!(
+                "Quieter far away: {}",
+                if mixer.distance_attenuation {
+                    "On"
+                } else {
+                    "Off"
+                }
+            ),
+            ButtonAction::Rebind(control)
+                if rebinding
+                    .as_ref()
+                    .is_some_and(|rebinding| rebinding.0 == control) =>
+            {
+                if control == GameControl::Pause {
+                    format!("{}: press a key", control.label())
+                } else {
+                    format!("{}: press a key, Esc to cancel", control.label())
+                }
+            }
+            ButtonAction::Rebind(control) if bindings.keys(control).is_empty() => {
+                format!("{}: unbound!", control.label())
+            }
+            ButtonAction::Rebind(control) => {
+                let keys: Vec<String> = bindings.keys(control).iter().map(key_name).collect();
+                format!("{}: {}", control.label(), keys.join(", "))
+            }
+            _ => continue,
+        };
+    }
+}
+
+// `KeyW` reads better as `W`, `Digit1` as `1`
+fn key_name(key: &KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
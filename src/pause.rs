@@ -0,0 +1,104 @@
+use bevy::{
+    input::{gamepad::GamepadConnectionEvent, InputSystem},
+    prelude::*,
+    window::WindowFocused,
+};
+
+use crate::{
+    actions::{track_gamepads, ActiveGamepad, Controls, GameControl},
+    GameState,
+};
+
+pub struct PausePlugin;
+
+// Pausing stops virtual time. Fixed ticks are paced by virtual time, so the whole of the gameplay,
+// timers included, stands still until the game is resumed.
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<PauseState>()
+            .add_systems(OnEnter(PauseState::Paused), pause_time)
+            .add_systems(OnExit(PauseState::Paused), resume_time)
+            .add_systems(
+                Update,
+                (
+                    toggle_pause,
+                    pause_on_focus_loss.run_if(resource_exists::<Events<WindowFocused>>),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                PreUpdate,
+                pause_on_gamepad_loss
+                    .after(InputSystem)
+                    .before(track_gamepads)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::Menu),
+                restart.run_if(resource_exists::<Restart>),
+            );
+    }
+}
+
+#[derive(SubStates, Default, Clone, Eq, PartialEq, Debug, Hash)]
+#[source(GameState = GameState::Playing)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Asks for a new game to start right after the current one is cleaned up in `GameState::Menu`
+#[derive(Resource)]
+pub(crate) struct Restart;
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+    info!("Paused");
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn toggle_pause(
+    controls: Controls,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if controls.just_pressed(GameControl::Pause) {
+        next_state.set(match state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if focus_events.read().any(|event| !event.focused) {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+// A controller running out of battery mid-game shouldn't cost a life. Runs before the next gamepad
+// takes over, and only minds the one the ship is flown with.
+fn pause_on_gamepad_loss(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    active: Res<ActiveGamepad>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if connection_events
+        .read()
+        .any(|event| !event.connected() && active.0 == Some(event.gamepad))
+    {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+fn restart(mut commands: Commands, mut next_state: ResMut<NextState<GameState>>) {
+    commands.remove_resource::<Restart>();
+    next_state.set(GameState::Playing);
+}
@@ -2,20 +2,31 @@ use bevy::prelude::*;
 
 use crate::{
     asteroids::{Asteroid, AsteroidSize},
+    bullets::{BulletFiredEvent, BulletHitEvent},
+    saucer::{Saucer, SaucerSize},
     ship::Ship,
-    GameState, Hit,
+    GameState, Hit, Kinematics,
 };
 
 pub struct PlayerPlugin;
 
+// Hits are counted before `Kinematics`, the hit entities are only despawned after it
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Playing), spawn_player)
             .add_systems(
                 FixedUpdate,
-                (handle_player_hit, handle_asteroid_hit).run_if(in_state(GameState::Playing)),
+                (
+                    handle_player_hit,
+                    handle_asteroid_hit,
+                    handle_saucer_hit,
+                    count_shots,
+                )
+                    .before(Kinematics)
+                    .run_if(in_state(GameState::Playing)),
             )
-            .add_systems(OnEnter(GameState::Menu), despawn_player);
+            .add_systems(OnEnter(GameState::Menu), despawn_player)
+            .add_systems(OnExit(GameState::GameOver), despawn_player);
     }
 }
 
@@ -23,6 +34,8 @@ impl Plugin for PlayerPlugin {
 pub struct Player {
     pub life_count: u8,
     pub score: i32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
 }
 
 impl Player {
@@ -30,8 +43,15 @@ impl Player {
         Self {
             life_count: 3,
             score: 0,
+            shots_fired: 0,
+            shots_hit: 0,
         }
     }
+
+    /// The share of shots fired that hit something, or `None` before the first shot
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots_fired > 0).then(|| self.shots_hit as f32 / self.shots_fired as f32)
+    }
 }
 
 fn spawn_player(mut commands: Commands) {
@@ -54,7 +74,7 @@ fn handle_player_hit(
             player.life_count -= 1;
 
             if player.life_count == 0 {
-                next_state.set(GameState::Menu);
+                next_state.set(GameState::GameOver);
             }
         }
     }
@@ -65,7 +85,7 @@ fn handle_asteroid_hit(
     asteroid_query: Query<(&AsteroidSize, &Hit), With<Asteroid>>,
 ) {
     if let Ok(mut player) = player_query.get_single_mut() {
-        for (size, _) in &asteroid_query {
+        for (size, _) in asteroid_query.iter().filter(|(_, &hit)| hit == Hit::Player) {
             player.score += match size {
                 AsteroidSize::Large => 20,
                 AsteroidSize::Medium => 50,
@@ -74,3 +94,29 @@ fn handle_asteroid_hit(
         }
     }
 }
+
+fn handle_saucer_hit(
+    mut player_query: Query<&mut Player>,
+    saucer_query: Query<(&SaucerSize, &Hit), With<Saucer>>,
+) {
+    if let Ok(mut player) = player_query.get_single_mut() {
+        for (size, _) in saucer_query.iter().filter(|(_, &hit)| hit == Hit::Player) {
+            player.score += match size {
+                SaucerSize::Large => 200,
+                SaucerSize::Small => 1000,
+            };
+        }
+    }
+}
+
+// Counted from the events, a bullet may hit something before a query would ever see it
+fn count_shots(
+    mut player_query: Query<&mut Player>,
+    mut bullet_fired: EventReader<BulletFiredEvent>,
+    mut bullet_hit: EventReader<BulletHitEvent>,
+) {
+    if let Ok(mut player) = player_query.get_single_mut() {
+        player.shots_fired += bullet_fired.read().count() as u32;
+        player.shots_hit += bullet_hit.read().count() as u32;
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::{draw::Fill, entity::ShapeBundle, prelude::GeometryBuilder, shapes};
+use rand::Rng;
+
+use crate::{
+    asteroids::Asteroid, player::Player, rng::GameRng, shield::Shield, ship::Ship, Arena, Collider,
+    CollisionSet, GameState, Hit, Kinematics, Position, ShipState, SpatialGrid, Velocity, Wrapping,
+};
+
+pub struct PowerUpsPlugin;
+
+// Destroyed asteroids now and then leave a pickup behind. Flying into it grants its effect, most
+// of them for a limited time only, or until the ship is destroyed.
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePowerUps>()
+            .add_systems(OnEnter(GameState::Playing), clear_power_ups)
+            .add_systems(OnEnter(ShipState::Destroyed), clear_power_ups)
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_pickups.before(Kinematics),
+                    (expire_pickups, expire_power_ups),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                collect_pickups
+                    .in_set(CollisionSet::NarrowPhase)
+                    .run_if(in_state(ShipState::Flying)),
+            )
+            .add_systems(OnEnter(GameState::Menu), despawn_pickups)
+            .add_systems(OnExit(GameState::GameOver), despawn_pickups);
+    }
+}
+
+const PICKUP_RADIUS: f32 = 8.;
+const PICKUP_SPEED: f32 = 32.;
+const PICKUP_LIFETIME_IN_SECONDS: u64 = 10;
+const PICKUP_DROP_CHANCE: f64 = 0.1;
+const POWER_UP_DURATION_IN_SECONDS: u64 = 10;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUp {
+    /// Every shot fires three bullets in a fan
+    SpreadShot,
+    /// Fires faster, with more bullets in the air at once
+    RapidFire,
+    /// Fills the ship's shield back up, right away
+    Shield,
+    /// Bullets fly on through what they hit
+    Piercing,
+    /// One more life, right away
+    ExtraLife,
+}
+
+impl PowerUp {
+    const ALL: [PowerUp; 5] = [
+        PowerUp::SpreadShot,
+        PowerUp::RapidFire,
+        PowerUp::Shield,
+        PowerUp::Piercing,
+        PowerUp::ExtraLife,
+    ];
+
+    fn color(&self) -> Color {
+        match self {
+            Self::SpreadShot => Color::srgb(1., 0.5, 0.),
+            Self::RapidFire => Color::srgb(1., 1., 0.),
+            Self::Shield => Color::srgb(0., 0.5, 1.),
+            Self::Piercing => Color::srgb(1., 0., 1.),
+            Self::ExtraLife => Color::srgb(0., 1., 0.),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::SpreadShot => "Spread",
+            Self::RapidFire => "Rapid",
+            Self::Shield => "Shield",
+            Self::Piercing => "Piercing",
+            Self::ExtraLife => "Life",
+        }
+    }
+}
+
+/// The timed power-ups in effect and the time they have left
+#[derive(Resource, Debug, Default)]
+pub struct ActivePowerUps(Vec<(PowerUp, Timer)>);
+
+impl ActivePowerUps {
+    pub fn is_active(&self, power_up: PowerUp) -> bool {
+        self.0.iter().any(|(active, _)| *active == power_up)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (PowerUp, Duration)> + '_ {
+        self.0
+            .iter()
+            .map(|(power_up, timer)| (*power_up, timer.remaining()))
+    }
+
+    /// Starts the power-up, or starts it over when it is already active
+    pub fn activate(&mut self, power_up: PowerUp, duration: Duration) {
+        self.0.retain(|(active, _)| *active != power_up);
+        self.0
+            .push((power_up, Timer::new(duration, TimerMode::Once)));
+    }
+}
+
+#[derive(Component)]
+struct Lifetime(Timer);
+
+#[derive(Bundle)]
+pub struct PickupBundle {
+    shape: ShapeBundle,
+    fill: Fill,
+    power_up: PowerUp,
+    velocity: Velocity,
+    wrapping: Wrapping,
+    collider: Collider,
+    lifetime: Lifetime,
+}
+
+impl PickupBundle {
+    pub fn new(position: Position, velocity: Velocity, power_up: PowerUp) -> Self {
+        let shape = shapes::Circle {
+            radius: PICKUP_RADIUS,
+            center: Vec2::ZERO,
+        };
+
+        Self {
+            shape: ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                spatial: SpatialBundle {
+                    transform: Transform {
+                        translation: position.0.extend(0.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            fill: Fill::color(power_up.color()),
+            power_up,
+            velocity,
+            wrapping: Wrapping,
+            collider: Collider::Circle(PICKUP_RADIUS),
+            lifetime: Lifetime(Timer::new(
+                Duration::from_secs(PICKUP_LIFETIME_IN_SECONDS),
+                TimerMode::Once,
+            )),
+        }
+    }
+}
+
+fn clear_power_ups(mut power_ups: ResMut<ActivePowerUps>) {
+    power_ups.0.clear();
+}
+
+fn drop_pickups(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    asteroid_query: Query<&Transform, (With<Asteroid>, With<Hit>)>,
+) {
+    for transform in &asteroid_query {
+        if !rng.gen_bool(PICKUP_DROP_CHANCE) {
+            continue;
+        }
+
+        let power_up = PowerUp::ALL[rng.gen_range(0..PowerUp::ALL.len())];
+        commands.spawn(PickupBundle::new(
+            Position(transform.translation.truncate()),
+            Velocity::random_with_speed(PICKUP_SPEED, &mut *rng),
+            power_up,
+        ));
+    }
+}
+
+fn expire_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickup_query: Query<(Entity, &mut Lifetime), With<PowerUp>>,
+) {
+    for (entity, mut lifetime) in &mut pickup_query {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn expire_power_ups(time: Res<Time>, mut power_ups: ResMut<ActivePowerUps>) {
+    for (_, timer) in &mut power_ups.0 {
+        timer.tick(time.delta());
+    }
+    power_ups.0.retain(|(_, timer)| !timer.finished());
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    grid: Res<SpatialGrid>,
+    mut power_ups: ResMut<ActivePowerUps>,
+    mut player_query: Query<&mut Player>,
+    mut ship_query: Query<(&Transform, &Collider, &mut Shield), With<Ship>>,
+    pickup_query: Query<(&Transform, &Collider, &PowerUp)>,
+) {
+    let Ok((ship_transform, ship_collider, mut shield)) = ship_query.get_single_mut() else {
+        return;
+    };
+
+    let candidates = grid.candidates(
+        ship_transform.translation.truncate(),
+        ship_collider.bounding_radius(),
+    );
+
+    for entity in candidates {
+        let Ok((pickup_transform, pickup_collider, &power_up)) = pickup_query.get(entity) else {
+            continue;
+        };
+
+        if !ship_collider.intersects_wrapped(
+            ship_transform,
+            pickup_collider,
+            pickup_transform,
+            &arena,
+        ) {
+            continue;
+        }
+
+        info!("Picked up {:?}", power_up);
+        commands.entity(entity).despawn_recursive();
+
+        match power_up {
+            PowerUp::ExtraLife => {
+                if let Ok(mut player) = player_query.get_single_mut() {
+                    player.life_count += 1;
+                }
+            }
+            PowerUp::Shield => shield.recharge(),
+            _ => power_ups.activate(power_up, Duration::from_secs(POWER_UP_DURATION_IN_SECONDS)),
+        }
+    }
+}
+
+fn despawn_pickups(mut commands: Commands, pickup_query: Query<Entity, With<PowerUp>>) {
+    for entity in &pickup_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{Actions, FiredAction},
+    bullets::BulletSettings,
+    rng::GameRng,
+    waves::DifficultyCurve,
+    Arena, GameState, Heading, Position,
+};
+
+#[derive(Clone)]
+pub enum ReplayMode {
+    Record(PathBuf),
+    Playback(Replay),
+}
+
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+}
+
+// Recording samples `Actions` and `FiredAction` at the start of every fixed tick of a game and
+// writes them, together with the game's seed and everything else the game plays out differently
+// with, to a file when the game ends, or when the app is closed mid-game. Playback sets the game up
+// the same way and writes the input back into the same resources at the same ticks, in place of
+// the keyboard.
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            ReplayMode::Record(path) => {
+                app.insert_resource(Recorder {
+                    path: path.clone(),
+                    setup: None,
+                    ticks: Vec::new(),
+                })
+                .add_systems(OnEnter(GameState::Playing), start_recording)
+                .add_systems(
+                    FixedPreUpdate,
+                    record_tick.run_if(in_state(GameState::Playing)),
+                )
+                .add_systems(OnExit(GameState::Playing), save_recording)
+                .add_systems(
+                    Last,
+                    save_recording
+                        .run_if(on_event::<AppExit>())
+                        .run_if(in_state(GameState::Playing)),
+                );
+            }
+            ReplayMode::Playback(replay) => {
+                app.insert_resource(replay.arena)
+                    .insert_resource(replay.difficulty.clone())
+                    .insert_resource(replay.bullets.clone())
+                    .insert_resource(Playback {
+                        replay: replay.clone(),
+                        tick: 0,
+                    })
+                    .add_systems(Startup, start_playback)
+                    .add_systems(OnEnter(GameState::Playing), rewind_playback)
+                    // ready for the replay to start over
+                    .add_systems(OnExit(GameState::Playing), restore_setup)
+                    .add_systems(
+                        FixedPreUpdate,
+                        play_tick.run_if(in_state(GameState::Playing)),
+                    );
+            }
+        }
+    }
+}
+
+/// A recorded game: the seed and settings it was played with and the player's input for every
+/// fixed tick.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    /// The arena at the start of the game, it follows the window size
+    pub arena: Arena,
+    pub difficulty: DifficultyCurve,
+    /// The bullet settings at the start of the game
+    pub bullets: BulletSettings,
+    pub ticks: Vec<TickInput>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let contents = ron::to_string(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, contents)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TickInput {
+    pub movement: Option<[f32; 2]>,
+    pub fired: Option<FiredInput>,
+    #[serde(default)]
+    pub shield: bool,
+    #[serde(default)]
+    pub hyperspace: bool,
+    /// The new arena, on the ticks the window was resized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arena: Option<Arena>,
+    /// The new bullet settings, on the ticks they were changed from the pause menu
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bullets: Option<BulletSettings>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FiredInput {
+    pub heading: [f32; 3],
+    pub position: [f32; 2],
+}
+
+impl TickInput {
+    fn capture(actions: &Actions, fired_action: &FiredAction) -> Self {
+        let fired = match (fired_action.heading, fired_action.position) {
+            (Some(heading), Some(position)) => Some(FiredInput {
+                heading: heading.0.to_array(),
+                position: position.0.to_array(),
+            }),
+            _ => None,
+        };
+
+        Self {
+            movement: actions.player_movement.map(|movement| movement.to_array()),
+            fired,
+            shield: actions.shield,
+            hyperspace: actions.hyperspace,
+            ..default()
+        }
+    }
+
+    fn apply(&self, actions: &mut Actions, fired_action: &mut FiredAction) {
+        actions.player_movement = self.movement.map(Vec2::from_array);
+        actions.shield = self.shield;
+        actions.hyperspace = self.hyperspace;
+        fired_action.heading = self
+            .fired
+            .map(|fired| Heading(Vec3::from_array(fired.heading)));
+        fired_action.position = self
+            .fired
+            .map(|fired| Position(Vec2::from_array(fired.position)));
+    }
+}
+
+#[derive(Resource)]
+struct Recorder {
+    path: PathBuf,
+    /// The arena, difficulty and bullet settings the game started with
+    setup: Option<(Arena, DifficultyCurve, BulletSettings)>,
+    ticks: Vec<TickInput>,
+}
+
+/// Present while a replay is fed into the game instead of the keyboard
+#[derive(Resource)]
+pub struct Playback {
+    replay: Replay,
+    tick: usize,
+}
+
+fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    arena: Res<Arena>,
+    difficulty: Res<DifficultyCurve>,
+    bullets: Res<BulletSettings>,
+) {
+    recorder.setup = Some((*arena, difficulty.clone(), bullets.clone()));
+    recorder.ticks.clear();
+}
+
+fn record_tick(
+    mut recorder: ResMut<Recorder>,
+    actions: Res<Actions>,
+    fired_action: Res<FiredAction>,
+    arena: Res<Arena>,
+    bullets: Res<BulletSettings>,
+) {
+    let mut input = TickInput::capture(&actions, &fired_action);
+    // changes since the previous tick, the first tick only repeats the setup
+    if arena.is_changed() {
+        input.arena = Some(*arena);
+    }
+    if bullets.is_changed() {
+        input.bullets = Some(bullets.clone());
+    }
+    recorder.ticks.push(input);
+}
+
+fn save_recording(mut recorder: ResMut<Recorder>, rng: Res<GameRng>) {
+    let Some((arena, difficulty, bullets)) = recorder.setup.take() else {
+        return;
+    };
+    let replay = Replay {
+        seed: rng.seed(),
+        arena,
+        difficulty,
+        bullets,
+        ticks: std::mem::take(&mut recorder.ticks),
+    };
+
+    match replay.save(&recorder.path) {
+        Ok(()) => info!(
+            "Saved replay of {} ticks to {}",
+            replay.ticks.len(),
+            recorder.path.display()
+        ),
+        Err(error) => error!(
+            "Could not save replay to {}: {}",
+            recorder.path.display(),
+            error
+        ),
+    }
+}
+
+// A replay starts right away, there is nobody to press the start button
+fn start_playback(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn rewind_playback(mut playback: ResMut<Playback>) {
+    playback.tick = 0;
+}
+
+fn restore_setup(
+    playback: Res<Playback>,
+    mut arena: ResMut<Arena>,
+    mut bullets: ResMut<BulletSettings>,
+) {
+    *arena = playback.replay.arena;
+    *bullets = playback.replay.bullets.clone();
+}
+
+fn play_tick(
+    mut playback: ResMut<Playback>,
+    mut actions: ResMut<Actions>,
+    mut fired_action: ResMut<FiredAction>,
+    mut arena: ResMut<Arena>,
+    mut bullets: ResMut<BulletSettings>,
+) {
+    let input = match playback.replay.ticks.get(playback.tick) {
+        Some(input) => input.clone(),
+        None => {
+            if playback.tick == playback.replay.ticks.len() {
+                info!("Replay finished");
+            }
+            TickInput::default()
+        }
+    };
+
+    input.apply(&mut actions, &mut fired_action);
+    if let Some(recorded) = input.arena {
+        *arena = recorded;
+    }
+    if let Some(recorded) = input.bullets {
+        *bullets = recorded;
+    }
+    playback.tick += 1;
+}
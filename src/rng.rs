@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use rand::{prelude::thread_rng, rngs::StdRng, Rng, RngCore, SeedableRng};
+
+use crate::GameState;
+
+pub struct RngPlugin {
+    pub seed: Option<u64>,
+}
+
+// Every bit of gameplay randomness is drawn from `GameRng`. It gets reseeded whenever a game starts,
+// so the same seed and the same inputs always play out the same game.
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameSeed(self.seed))
+            .insert_resource(GameRng::new(self.seed.unwrap_or_else(random_seed)))
+            .add_systems(OnEnter(GameState::Playing), reseed);
+    }
+}
+
+/// The seed every game is started with. A new random seed is picked per game when `None`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSeed(pub Option<u64>);
+
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The seed the current game was started with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
+fn reseed(mut commands: Commands, seed: Res<GameSeed>) {
+    let seed = seed.0.unwrap_or_else(random_seed);
+    info!("Starting game with seed {}", seed);
+
+    commands.insert_resource(GameRng::new(seed));
+}
@@ -0,0 +1,383 @@
+use std::{f32::consts::PI, ops::Range};
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::{draw::Fill, entity::ShapeBundle, prelude::GeometryBuilder, shapes};
+use rand::Rng;
+
+use crate::{
+    asteroids::{Asteroid, BangLargeEvent},
+    bullets::{Bullet, BulletHitEvent, BulletSettings, EnemyBullet},
+    player::Player,
+    rng::GameRng,
+    shield::Shield,
+    ship::Ship,
+    Arena, BulletBundle, Collider, CollisionSet, GameState, Heading, Hit, Kinematics, Position,
+    SpatialGrid, Velocity, Wrapping,
+};
+
+pub struct SaucerPlugin;
+
+// Every now and then a saucer enters from the left or right edge and zig-zags across the arena,
+// shooting at the ship, until it leaves over the opposite edge or is destroyed.
+impl Plugin for SaucerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaucerSpawnTimer>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawn_timer)
+            .add_systems(
+                FixedUpdate,
+                (
+                    (spawn_saucer, zig_zag, fire, sound_siren).before(Kinematics),
+                    (leave, handle_hit).after(Kinematics),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                detect_collisions
+                    .in_set(CollisionSet::NarrowPhase)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Menu), despawn_saucers)
+            .add_systems(OnExit(GameState::GameOver), despawn_saucers)
+            .add_event::<SaucerSirenEvent>();
+    }
+}
+
+const SAUCER_COLOR: Color = Color::srgb(1., 0., 0.);
+const SAUCER_RADIUS_LARGE: f32 = 20.;
+const SAUCER_RADIUS_SMALL: f32 = 10.;
+const SAUCER_SPEED_LARGE: f32 = 96.;
+const SAUCER_SPEED_SMALL: f32 = 144.;
+// How far off the ship a shot may go, either way, in radians
+const SAUCER_INACCURACY_LARGE: f32 = PI / 6.;
+const SAUCER_INACCURACY_SMALL: f32 = PI / 24.;
+const SAUCER_FIRE_INTERVAL_LARGE: f32 = 1.5;
+const SAUCER_FIRE_INTERVAL_SMALL: f32 = 1.;
+const SAUCER_ZIG_ZAG_INTERVAL: f32 = 1.;
+const SAUCER_SIREN_INTERVAL: f32 = 0.25;
+// Seconds without a saucer before the next one shows up
+const SAUCER_SPAWN_INTERVAL: Range<f32> = 10.0..20.0;
+const SMALL_SAUCER_CHANCE: f64 = 0.2;
+// From this score on only small saucers show up
+const SMALL_SAUCER_ONLY_SCORE: i32 = 10_000;
+
+#[derive(Event)]
+pub(crate) struct SaucerSirenEvent {
+    pub size: SaucerSize,
+    pub position: Vec2,
+}
+
+#[derive(Component)]
+pub struct Saucer;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaucerSize {
+    Large,
+    Small,
+}
+
+impl SaucerSize {
+    fn radius(&self) -> f32 {
+        match self {
+            Self::Large => SAUCER_RADIUS_LARGE,
+            Self::Small => SAUCER_RADIUS_SMALL,
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        match self {
+            Self::Large => SAUCER_SPEED_LARGE,
+            Self::Small => SAUCER_SPEED_SMALL,
+        }
+    }
+
+    fn inaccuracy(&self) -> f32 {
+        match self {
+            Self::Large => SAUCER_INACCURACY_LARGE,
+            Self::Small => SAUCER_INACCURACY_SMALL,
+        }
+    }
+
+    fn fire_interval(&self) -> f32 {
+        match self {
+            Self::Large => SAUCER_FIRE_INTERVAL_LARGE,
+            Self::Small => SAUCER_FIRE_INTERVAL_SMALL,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SaucerSpawnTimer(Timer);
+
+impl Default for SaucerSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            SAUCER_SPAWN_INTERVAL.start,
+            TimerMode::Once,
+        ))
+    }
+}
+
+// The horizontal distance the saucer has left to fly before it leaves the arena
+#[derive(Component)]
+struct Crossing(f32);
+
+#[derive(Component)]
+struct ZigZag(Timer);
+
+#[derive(Component)]
+struct FireTimer(Timer);
+
+#[derive(Component)]
+struct Siren(Timer);
+
+#[derive(Bundle)]
+pub struct SaucerBundle {
+    shape: ShapeBundle,
+    fill: Fill,
+    saucer: Saucer,
+    velocity: Velocity,
+    wrapping: Wrapping,
+    collider: Collider,
+    size: SaucerSize,
+    zig_zag: ZigZag,
+    fire_timer: FireTimer,
+    siren: Siren,
+}
+
+impl SaucerBundle {
+    /// A saucer flying to the right, or to the left if `direction` is negative
+    pub fn new(position: Position, direction: f32, size: SaucerSize) -> Self {
+        let shape = shapes::Circle {
+            radius: size.radius(),
+            center: Vec2::ZERO,
+        };
+
+        Self {
+            shape: ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                spatial: SpatialBundle {
+                    transform: Transform {
+                        translation: position.0.extend(0.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            fill: Fill::color(SAUCER_COLOR),
+            saucer: Saucer,
+            velocity: Velocity(Vec3::X * direction.signum() * size.speed()),
+            wrapping: Wrapping,
+            collider: Collider::Circle(size.radius()),
+            size,
+            zig_zag: ZigZag(Timer::from_seconds(
+                SAUCER_ZIG_ZAG_INTERVAL,
+                TimerMode::Repeating,
+            )),
+            fire_timer: FireTimer(Timer::from_seconds(
+                size.fire_interval(),
+                TimerMode::Repeating,
+            )),
+            siren: Siren(Timer::from_seconds(
+                SAUCER_SIREN_INTERVAL,
+                TimerMode::Repeating,
+            )),
+        }
+    }
+}
+
+fn reset_spawn_timer(mut spawn_timer: ResMut<SaucerSpawnTimer>) {
+    *spawn_timer = SaucerSpawnTimer::default();
+}
+
+fn spawn_saucer(
+    mut commands: Commands,
+    time: Res<Time>,
+    arena: Res<Arena>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_timer: ResMut<SaucerSpawnTimer>,
+    player_query: Query<&Player>,
+    saucer_query: Query<(), With<Saucer>>,
+) {
+    if !saucer_query.is_empty() || !spawn_timer.0.tick(time.delta()).finished() {
+        return;
+    }
+
+    let score = player_query.get_single().map_or(0, |player| player.score);
+    let size = if score >= SMALL_SAUCER_ONLY_SCORE || rng.gen_bool(SMALL_SAUCER_CHANCE) {
+        SaucerSize::Small
+    } else {
+        SaucerSize::Large
+    };
+    let direction = if rng.gen_bool(0.5) { 1. } else { -1. };
+    let half_extents = arena.half_extents();
+    let position = Vec2::new(
+        -direction * half_extents.x,
+        rng.gen_range(-half_extents.y..half_extents.y),
+    );
+
+    info!("Spawning {:?} saucer", size);
+    commands
+        .spawn(SaucerBundle::new(Position(position), direction, size))
+        .insert(Crossing(arena.width));
+
+    spawn_timer.0 = Timer::from_seconds(rng.gen_range(SAUCER_SPAWN_INTERVAL), TimerMode::Once);
+}
+
+// Every so often the saucer picks a new course: straight on, or diagonally up or down
+fn zig_zag(
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+    mut saucer_query: Query<(&mut ZigZag, &mut Velocity, &SaucerSize), With<Saucer>>,
+) {
+    for (mut zig_zag, mut velocity, size) in &mut saucer_query {
+        if zig_zag.0.tick(time.delta()).just_finished() {
+            velocity.0.y = rng.gen_range(-1..=1) as f32 * size.speed();
+        }
+    }
+}
+
+fn fire(
+    mut commands: Commands,
+    time: Res<Time>,
+    arena: Res<Arena>,
+    settings: Res<BulletSettings>,
+    mut rng: ResMut<GameRng>,
+    mut saucer_query: Query<(&mut FireTimer, &Transform, &SaucerSize), With<Saucer>>,
+    ship_query: Query<&Transform, (With<Ship>, With<Collider>)>,
+) {
+    for (mut fire_timer, transform, size) in &mut saucer_query {
+        if !fire_timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        // without a ship to aim at, gone or in hyperspace, the saucer fires anywhere
+        let aim = match ship_query.get_single() {
+            Ok(ship_transform) => {
+                let target = arena.nearest_image(position, ship_transform.translation.truncate());
+                (target - position).to_angle()
+                    + rng.gen_range(-size.inaccuracy()..=size.inaccuracy())
+            }
+            Err(_) => rng.gen_range(-PI..PI),
+        };
+
+        let mut bullet = commands.spawn((
+            BulletBundle::new(
+                Heading(Vec2::from_angle(aim).extend(0.)),
+                Position(position),
+            )
+            .with_lifetime(settings.lifetime),
+            EnemyBullet,
+        ));
+        if settings.wrap {
+            bullet.insert(Wrapping);
+        }
+    }
+}
+
+fn leave(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut saucer_query: Query<(Entity, &mut Crossing, &Velocity), With<Saucer>>,
+) {
+    for (entity, mut crossing, velocity) in &mut saucer_query {
+        crossing.0 -= velocity.0.x.abs() * time.delta_seconds();
+
+        if crossing.0 <= 0. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn sound_siren(
+    time: Res<Time>,
+    mut saucer_query: Query<(&mut Siren, &SaucerSize, &Transform), With<Saucer>>,
+    mut siren_event: EventWriter<SaucerSirenEvent>,
+) {
+    for (mut siren, &size, transform) in &mut saucer_query {
+        if siren.0.tick(time.delta()).just_finished() {
+            siren_event.send(SaucerSirenEvent {
+                size,
+                position: transform.translation.truncate(),
+            });
+        }
+    }
+}
+
+// Saucers are shot by the ship's bullets, crash into the ship, and smash any asteroid they meet
+#[allow(clippy::too_many_arguments)]
+fn detect_collisions(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    grid: Res<SpatialGrid>,
+    saucer_query: Query<(Entity, &Transform, &Collider), With<Saucer>>,
+    bullet_query: Query<(&Transform, &Collider, Has<EnemyBullet>), With<Bullet>>,
+    mut ship_query: Query<(&Transform, &Collider, &mut Shield), With<Ship>>,
+    asteroid_query: Query<(&Transform, &Collider), With<Asteroid>>,
+    mut bullet_hit: EventWriter<BulletHitEvent>,
+) {
+    for (saucer_entity, saucer_transform, saucer_collider) in saucer_query.iter() {
+        let candidates = grid.candidates(
+            saucer_transform.translation.truncate(),
+            saucer_collider.bounding_radius(),
+        );
+
+        for entity in candidates {
+            if let Ok((transform, collider, mut shield)) = ship_query.get_mut(entity) {
+                if saucer_collider.intersects_wrapped(saucer_transform, collider, transform, &arena)
+                {
+                    commands.entity(saucer_entity).try_insert(Hit::Player);
+                    // the shield takes the crash, like it would an asteroid
+                    if shield.is_up() {
+                        shield.take_impact();
+                    } else {
+                        commands.entity(entity).try_insert(Hit::Enemy);
+                    }
+                }
+                continue;
+            }
+
+            let (transform, collider, saucer_hit, other_hit) =
+                if let Ok((transform, collider, false)) = bullet_query.get(entity) {
+                    (transform, collider, Hit::Player, None)
+                } else if let Ok((transform, collider)) = asteroid_query.get(entity) {
+                    (transform, collider, Hit::Enemy, Some(Hit::Enemy))
+                } else {
+                    continue;
+                };
+
+            if saucer_collider.intersects_wrapped(saucer_transform, collider, transform, &arena) {
+                commands.entity(saucer_entity).try_insert(saucer_hit);
+                match other_hit {
+                    Some(hit) => {
+                        commands.entity(entity).try_insert(hit);
+                    }
+                    None => {
+                        commands.entity(entity).despawn_recursive();
+                        bullet_hit.send_default();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn handle_hit(
+    mut commands: Commands,
+    hit_query: Query<(Entity, &Transform, &Hit), With<Saucer>>,
+    mut bang_event: EventWriter<BangLargeEvent>,
+) {
+    for (entity, transform, _) in &hit_query {
+        info!("Saucer destroyed");
+        bang_event.send(BangLargeEvent(transform.translation.truncate()));
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn despawn_saucers(mut commands: Commands, saucer_query: Query<Entity, With<Saucer>>) {
+    for entity in &saucer_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
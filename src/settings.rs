@@ -0,0 +1,116 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{GamepadConfig, KeyBindings},
+    bullets::BulletSettings,
+    replay::Playback,
+    sounds::VolumeMixer,
+};
+
+pub struct SettingsPlugin {
+    pub path: Option<PathBuf>,
+}
+
+// The settings are read once at startup and written back whenever the player changes one of them.
+// A replay plays with the settings it was recorded with, those are not the player's to keep.
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = match &self.path {
+            Some(path) => Settings::load(path).unwrap_or_else(|error| {
+                if error.kind() != io::ErrorKind::NotFound {
+                    warn!("Could not load settings {}: {}", path.display(), error);
+                }
+                Settings::default()
+            }),
+            None => Settings::default(),
+        };
+
+        app.insert_resource(settings.key_bindings)
+            .insert_resource(settings.gamepad)
+            .insert_resource(settings.volume)
+            .insert_resource(settings.bullets)
+            .insert_resource(SettingsFile(self.path.clone()))
+            .add_systems(
+                Update,
+                save_settings
+                    .run_if(settings_changed)
+                    .run_if(not(resource_exists::<Playback>)),
+            );
+    }
+}
+
+/// Everything the player can set up, as kept in the settings file
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub key_bindings: KeyBindings,
+    pub gamepad: GamepadConfig,
+    pub volume: VolumeMixer,
+    pub bullets: BulletSettings,
+}
+
+impl Settings {
+    /// Where the settings are kept unless told otherwise, in the user's config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("asteroids").join("settings.ron"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(self, default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, contents)
+    }
+}
+
+#[derive(Resource)]
+struct SettingsFile(Option<PathBuf>);
+
+// Loading the settings isn't a change, they are already in the file
+fn settings_changed(
+    key_bindings: Res<KeyBindings>,
+    gamepad: Res<GamepadConfig>,
+    volume: Res<VolumeMixer>,
+    bullets: Res<BulletSettings>,
+) -> bool {
+    (key_bindings.is_changed() && !key_bindings.is_added())
+        || (gamepad.is_changed() && !gamepad.is_added())
+        || (volume.is_changed() && !volume.is_added())
+        || (bullets.is_changed() && !bullets.is_added())
+}
+
+fn save_settings(
+    file: Res<SettingsFile>,
+    key_bindings: Res<KeyBindings>,
+    gamepad: Res<GamepadConfig>,
+    volume: Res<VolumeMixer>,
+    bullets: Res<BulletSettings>,
+) {
+    let Some(path) = &file.0 else {
+        return;
+    };
+
+    let settings = Settings {
+        key_bindings: key_bindings.clone(),
+        gamepad: *gamepad,
+        volume: *volume,
+        bullets: bullets.clone(),
+    };
+    if let Err(error) = settings.save(path) {
+        error!("Could not save settings {}: {}", path.display(), error);
+    }
+}
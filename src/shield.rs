@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{
+    actions::Actions, asteroids::Asteroid, bullets::EnemyBullet, ship::Ship, Arena, Collider,
+    CollisionSet, GameState, Kinematics, ShipState, SpatialGrid, Velocity,
+};
+
+pub struct ShieldPlugin;
+
+// While the shield control is held and there is energy left, the shield keeps asteroids and enemy
+// bullets off the ship. `ship::detect_collisions` leaves a shielded ship alone, the shield deals
+// with everything that touches it here instead.
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            charge
+                .before(Kinematics)
+                .run_if(in_state(ShipState::Flying)),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            deflect
+                .in_set(CollisionSet::NarrowPhase)
+                .run_if(in_state(ShipState::Flying)),
+        )
+        .add_systems(
+            Update,
+            (spawn_bubble, show_bubble).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+const SHIELD_COLOR: Color = Color::srgb(0., 0.5, 1.);
+const SHIELD_MAX_ENERGY: f32 = 1.;
+// Energy per second
+const SHIELD_DRAIN: f32 = 0.2;
+const SHIELD_RECHARGE: f32 = 0.05;
+// Energy per asteroid or bullet the shield takes
+const SHIELD_IMPACT_DRAIN: f32 = 0.25;
+// How much bigger the bubble is than the ship it protects
+const SHIELD_MARGIN: f32 = 5.;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Shield {
+    pub energy: f32,
+    pub active: bool,
+}
+
+impl Default for Shield {
+    fn default() -> Self {
+        Self {
+            energy: SHIELD_MAX_ENERGY,
+            active: false,
+        }
+    }
+}
+
+impl Shield {
+    /// Whether the shield is up and takes whatever hits the ship
+    pub fn is_up(&self) -> bool {
+        self.active && self.energy > 0.
+    }
+
+    /// The energy left, from 0 to 1
+    pub fn charge(&self) -> f32 {
+        self.energy / SHIELD_MAX_ENERGY
+    }
+
+    pub fn recharge(&mut self) {
+        self.energy = SHIELD_MAX_ENERGY;
+    }
+
+    /// Takes the energy it costs to stop an asteroid, a bullet or a saucer
+    pub(crate) fn take_impact(&mut self) {
+        self.energy = (self.energy - SHIELD_IMPACT_DRAIN).max(0.);
+    }
+}
+
+type DeflectedAsteroids<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, &'static Collider, &'static mut Velocity),
+    (With<Asteroid>, Without<Ship>),
+>;
+
+#[derive(Component)]
+struct Bubble;
+
+fn charge(
+    time: Res<Time>,
+    actions: Res<Actions>,
+    mut shield_query: Query<&mut Shield, With<Ship>>,
+) {
+    for mut shield in &mut shield_query {
+        shield.active = actions.shield;
+
+        // it only recharges while switched off
+        let rate = if shield.active {
+            -SHIELD_DRAIN
+        } else {
+            SHIELD_RECHARGE
+        };
+        shield.energy = (shield.energy + rate * time.delta_seconds()).clamp(0., SHIELD_MAX_ENERGY);
+    }
+}
+
+// Asteroids bounce off the shield, enemy bullets vanish into it, until the energy runs out
+fn deflect(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    grid: Res<SpatialGrid>,
+    mut ship_query: Query<(&Transform, &Collider, &Velocity, &mut Shield), With<Ship>>,
+    mut asteroid_query: DeflectedAsteroids,
+    enemy_bullet_query: Query<(&Transform, &Collider), With<EnemyBullet>>,
+) {
+    let Ok((ship_transform, ship_collider, ship_velocity, mut shield)) =
+        ship_query.get_single_mut()
+    else {
+        return;
+    };
+    let ship_position = ship_transform.translation.truncate();
+    let candidates = grid.candidates(ship_position, ship_collider.bounding_radius());
+
+    for entity in candidates {
+        if !shield.is_up() {
+            break;
+        }
+
+        if let Ok((transform, collider, mut velocity)) = asteroid_query.get_mut(entity) {
+            if !ship_collider.intersects_wrapped(ship_transform, collider, transform, &arena) {
+                continue;
+            }
+
+            // reflect the asteroid off the ship, unless it is already on its way out
+            let normal = (arena.nearest_image(ship_position, transform.translation.truncate())
+                - ship_position)
+                .normalize_or_zero()
+                .extend(0.);
+            let relative = velocity.0 - ship_velocity.0;
+            let approach = relative.dot(normal);
+            if approach < 0. {
+                velocity.0 -= 2. * approach * normal;
+                shield.take_impact();
+            }
+        } else if let Ok((transform, collider)) = enemy_bullet_query.get(entity) {
+            if ship_collider.intersects_wrapped(ship_transform, collider, transform, &arena) {
+                commands.entity(entity).despawn_recursive();
+                shield.take_impact();
+            }
+        }
+    }
+}
+
+fn spawn_bubble(mut commands: Commands, ship_query: Query<(Entity, &Collider), Added<Shield>>) {
+    for (ship, collider) in &ship_query {
+        let shape = shapes::Circle {
+            radius: collider.bounding_radius() + SHIELD_MARGIN,
+            center: Vec2::ZERO,
+        };
+
+        commands.entity(ship).with_children(|parent| {
+            parent.spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&shape),
+                    spatial: SpatialBundle {
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Stroke::new(SHIELD_COLOR, 2.),
+                Bubble,
+            ));
+        });
+    }
+}
+
+fn show_bubble(
+    shield_query: Query<(&Shield, &Children)>,
+    mut bubble_query: Query<&mut Visibility, With<Bubble>>,
+) {
+    for (shield, children) in &shield_query {
+        for &child in children {
+            if let Ok(mut visibility) = bubble_query.get_mut(child) {
+                *visibility = if shield.is_up() {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
@@ -1,14 +1,17 @@
 use std::time::Duration;
 
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
-};
+use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
+use rand::Rng;
 
 use crate::{
-    actions::Actions, asteroids::Asteroid, Collider, GameState, Heading, Hit, Position, Velocity,
-    Wrapping,
+    actions::Actions,
+    asteroids::Asteroid,
+    bullets::{EnemyBullet, FireCooldown},
+    rng::GameRng,
+    shield::Shield,
+    Arena, Collider, CollisionSet, GameState, Heading, Hit, Kinematics, Position, SpatialGrid,
+    Velocity, Wrapping,
 };
 
 pub struct ShipPlugin;
@@ -16,39 +19,106 @@ pub struct ShipPlugin;
 impl Plugin for ShipPlugin {
     fn build(&self, app: &mut App) {
         app.add_sub_state::<ShipState>()
-            .add_systems(OnEnter(ShipState::Flying), spawn_ship)
+            .init_resource::<HyperspaceSettings>()
+            .add_systems(OnEnter(ShipState::Flying), (spawn_ship, forget_hyperspace))
             .add_systems(
-                Update,
-                (rotate, accelerate, detect_collisions, gizmo_draw_aiming)
+                FixedUpdate,
+                (rotate, accelerate, jump)
+                    .before(Kinematics)
+                    .run_if(in_state(ShipState::Flying)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                detect_collisions
+                    .in_set(CollisionSet::NarrowPhase)
                     .run_if(in_state(ShipState::Flying)),
             )
+            .add_systems(OnEnter(ShipState::Hyperspace), vanish)
             .add_systems(
                 FixedUpdate,
-                (handle_hit, displace).run_if(in_state(GameState::Playing)),
+                hyperspace_timer.run_if(in_state(ShipState::Hyperspace)),
+            )
+            // re-entry can blow the ship up, like a collision would
+            .add_systems(
+                FixedPostUpdate,
+                reenter
+                    .in_set(CollisionSet::NarrowPhase)
+                    .run_if(in_state(ShipState::Hyperspace)),
+            )
+            .add_systems(
+                Update,
+                gizmo_draw_aiming
+                    .run_if(in_state(ShipState::Flying))
+                    .run_if(resource_exists::<GizmoConfigStore>),
             )
-            .add_systems(OnEnter(ShipState::Destroyed), (despawn_ship, destroy))
+            .add_systems(
+                FixedUpdate,
+                handle_hit
+                    .after(Kinematics)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(ShipState::Destroyed),
+                (despawn_ship, destroy, forget_hyperspace),
+            )
+            .add_systems(OnExit(ShipState::Destroyed), despawn_wreck)
             .add_systems(OnEnter(GameState::Menu), despawn_ship)
-            .add_systems(Update, respawn_timer.run_if(in_state(ShipState::Destroyed)))
-            .add_event::<ThrustEvent>();
+            .add_systems(OnExit(GameState::GameOver), despawn_ship)
+            .add_systems(
+                FixedUpdate,
+                respawn_timer.run_if(in_state(ShipState::Destroyed)),
+            )
+            .add_event::<HyperspaceEvent>();
     }
 }
 
 const SHIP_COLOR: Color = Color::srgb(0., 1., 0.);
-const SHIP_SPEED: f32 = 300.;
+const SHIP_ACCELERATION: f32 = 300.;
 const SHIP_RADIUS: f32 = 15.;
 const ROTATION_SPEED: f32 = 7.;
 const RESPAWN_TIME_IN_SECONDS: u64 = 3;
 
 #[derive(SubStates, Default, Clone, Eq, PartialEq, Debug, Hash)]
 #[source(GameState = GameState::Playing)]
-enum ShipState {
+pub enum ShipState {
     #[default]
     Flying,
+    /// Gone from the arena for a moment after a hyperspace jump
+    Hyperspace,
     Destroyed,
 }
 
-#[derive(Event, Default)]
-pub(crate) struct ThrustEvent;
+/// Whether the ship's engine is firing
+#[derive(Component, Default, PartialEq)]
+pub struct Thrusting(pub bool);
+
+/// Each carries where the ship jumped from or came back in
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum HyperspaceEvent {
+    Jumped(Vec2),
+    Reappeared(Vec2),
+    Exploded(Vec2),
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct HyperspaceSettings {
+    /// How long the ship is gone before it reappears
+    pub vanish_time: Duration,
+    /// Chance of the ship blowing up when it reappears, from 0 to 1
+    pub explode_chance: f64,
+    /// Flying time after a jump before the next one
+    pub cooldown: Duration,
+}
+
+impl Default for HyperspaceSettings {
+    fn default() -> Self {
+        Self {
+            vanish_time: Duration::from_millis(500),
+            explode_chance: 0.1,
+            cooldown: Duration::from_secs(2),
+        }
+    }
+}
 
 #[derive(Component)]
 pub struct Ship;
@@ -62,6 +132,9 @@ pub struct ShipBundle {
     heading: Heading,
     wrapping: Wrapping,
     collider: Collider,
+    shield: Shield,
+    fire_cooldown: FireCooldown,
+    thrusting: Thrusting,
 }
 
 impl ShipBundle {
@@ -82,12 +155,20 @@ impl ShipBundle {
             velocity: Velocity(Vec3::ZERO),
             heading: Heading(Vec3::ZERO),
             wrapping: Wrapping,
-            collider: Collider,
+            collider: Collider::Triangle(radius),
+            shield: Shield::default(),
+            fire_cooldown: FireCooldown::default(),
+            thrusting: Thrusting::default(),
         }
     }
 }
 
-fn spawn_ship(mut commands: Commands) {
+fn spawn_ship(mut commands: Commands, ship_query: Query<(), With<Ship>>) {
+    // coming back from hyperspace, the ship is still around
+    if !ship_query.is_empty() {
+        return;
+    }
+
     info!("Spawning ship");
 
     commands.spawn(ShipBundle::new(SHIP_RADIUS));
@@ -109,63 +190,175 @@ fn rotate(
     }
 
     for mut transform in &mut ship_query {
-        transform.rotate_z(
-            (actions.player_movement.unwrap().x * -1.) * ROTATION_SPEED * time.delta_seconds(),
-        );
+        transform
+            .rotate_z(-actions.player_movement.unwrap().x * ROTATION_SPEED * time.delta_seconds());
     }
 }
 
 fn accelerate(
     time: Res<Time>,
     actions: Res<Actions>,
-    mut ship_query: Query<(&mut Velocity, &mut Heading, &Transform), With<Ship>>,
-    mut thrust_event: EventWriter<ThrustEvent>,
+    mut ship_query: Query<(&mut Velocity, &mut Heading, &mut Thrusting, &Transform), With<Ship>>,
 ) {
-    if actions.player_movement.is_none() {
-        return;
-    }
+    let direction = actions.player_movement.map_or(0., |movement| movement.y);
 
-    for (mut velocity, mut heading, transform) in &mut ship_query {
-        let direction = actions.player_movement.unwrap().y;
+    for (mut velocity, mut heading, mut thrusting, transform) in &mut ship_query {
+        thrusting.set_if_neq(Thrusting(direction > 0.));
         if direction > 0. {
-            let velocity_change = direction * time.delta_seconds();
+            let velocity_change = direction * SHIP_ACCELERATION * time.delta_seconds();
             let new_heading = transform.rotation * Vec3::Y;
             let new_velocity = velocity.0 + new_heading * velocity_change;
 
             velocity.0 = new_velocity;
             heading.0 = new_heading;
+        }
+    }
+}
+
+#[derive(Component)]
+struct HyperspaceCooldown(Timer);
+
+#[derive(Component)]
+struct Vanished(Timer);
 
-            thrust_event.send_default();
+fn jump(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut actions: ResMut<Actions>,
+    mut ship_query: Query<(Entity, &Transform, Option<&mut HyperspaceCooldown>), With<Ship>>,
+    mut next_state: ResMut<NextState<ShipState>>,
+    mut hyperspace_event: EventWriter<HyperspaceEvent>,
+) {
+    // a press while the ship can't jump is dropped, not kept for later
+    let jump = std::mem::take(&mut actions.hyperspace);
+    let Ok((ship, transform, cooldown)) = ship_query.get_single_mut() else {
+        return;
+    };
+
+    if let Some(mut cooldown) = cooldown {
+        if !cooldown.0.tick(time.delta()).finished() {
+            return;
         }
+        commands.entity(ship).remove::<HyperspaceCooldown>();
+    }
+
+    if jump {
+        next_state.set(ShipState::Hyperspace);
+        hyperspace_event.send(HyperspaceEvent::Jumped(transform.translation.truncate()));
     }
 }
 
-// Todo: This should probably be extracted as it is the same logic for the asteroids
-fn displace(time: Res<Time>, mut ship_query: Query<(&mut Transform, &Velocity), With<Ship>>) {
-    for (mut transform, velocity) in &mut ship_query {
-        let translation_delta = SHIP_SPEED * velocity.0 * time.delta_seconds();
-        transform.translation += translation_delta;
+// A press while there was no ship to jump with would otherwise send the next one off right away
+fn forget_hyperspace(mut actions: ResMut<Actions>) {
+    actions.hyperspace = false;
+}
+
+fn vanish(
+    mut commands: Commands,
+    settings: Res<HyperspaceSettings>,
+    mut ship_query: Query<(Entity, &mut Visibility, &mut Velocity, &mut Thrusting), With<Ship>>,
+) {
+    if let Ok((ship, mut visibility, mut velocity, mut thrusting)) = ship_query.get_single_mut() {
+        info!("Ship jumped into hyperspace");
+        *visibility = Visibility::Hidden;
+        velocity.0 = Vec3::ZERO;
+        thrusting.0 = false;
+        commands
+            .entity(ship)
+            .remove::<Collider>()
+            .insert(Vanished(Timer::new(settings.vanish_time, TimerMode::Once)));
+    }
+}
+
+fn hyperspace_timer(time: Res<Time>, mut vanished_query: Query<&mut Vanished>) {
+    for mut vanished in &mut vanished_query {
+        vanished.0.tick(time.delta());
+    }
+}
+
+fn reenter(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    settings: Res<HyperspaceSettings>,
+    mut rng: ResMut<GameRng>,
+    mut ship_query: Query<(Entity, &Vanished, &mut Transform, &mut Visibility), With<Ship>>,
+    mut next_state: ResMut<NextState<ShipState>>,
+    mut hyperspace_event: EventWriter<HyperspaceEvent>,
+) {
+    let Ok((ship, vanished, mut transform, mut visibility)) = ship_query.get_single_mut() else {
+        return;
+    };
+
+    if !vanished.0.finished() {
+        return;
+    }
+
+    let half_extents = arena.half_extents();
+    let position = Vec2::new(
+        rng.gen_range(-half_extents.x..half_extents.x),
+        rng.gen_range(-half_extents.y..half_extents.y),
+    );
+    transform.translation = position.extend(transform.translation.z);
+    *visibility = Visibility::Inherited;
+
+    let mut ship = commands.entity(ship);
+    ship.remove::<Vanished>()
+        .insert(Collider::Triangle(SHIP_RADIUS))
+        .insert(HyperspaceCooldown(Timer::new(
+            settings.cooldown,
+            TimerMode::Once,
+        )));
+
+    if rng.gen_bool(settings.explode_chance) {
+        info!("Ship exploded on re-entry");
+        ship.insert(Hit::Enemy);
+        hyperspace_event.send(HyperspaceEvent::Exploded(position));
+    } else {
+        next_state.set(ShipState::Flying);
+        hyperspace_event.send(HyperspaceEvent::Reappeared(position));
     }
 }
 
 fn detect_collisions(
     mut commands: Commands,
-    ship_query: Query<(Entity, &Transform), With<Ship>>,
-    asteroid_query: Query<(Entity, &Transform), With<Asteroid>>,
+    arena: Res<Arena>,
+    grid: Res<SpatialGrid>,
+    ship_query: Query<(Entity, &Transform, &Collider, &Shield), With<Ship>>,
+    asteroid_query: Query<(&Transform, &Collider), With<Asteroid>>,
+    enemy_bullet_query: Query<(&Transform, &Collider), With<EnemyBullet>>,
 ) {
-    for (ship_entity, ship_transform) in ship_query.iter() {
-        let ship_bounding_box =
-            Aabb2d::new(ship_transform.translation.truncate(), Vec2::new(15., 15.));
-
-        for (asteroid_entity, asteroid_transform) in asteroid_query.iter() {
-            let asteroid_bounding_box = Aabb2d::new(
-                asteroid_transform.translation.truncate(),
-                Vec2::new(20., 20.),
-            );
-
-            if ship_bounding_box.intersects(&asteroid_bounding_box) {
-                commands.entity(asteroid_entity).insert(Hit);
-                commands.entity(ship_entity).insert(Hit);
+    for (ship_entity, ship_transform, ship_collider, shield) in ship_query.iter() {
+        // the shield takes care of what hits it
+        if shield.is_up() {
+            continue;
+        }
+
+        let candidates = grid.candidates(
+            ship_transform.translation.truncate(),
+            ship_collider.bounding_radius(),
+        );
+
+        for entity in candidates {
+            if let Ok((asteroid_transform, asteroid_collider)) = asteroid_query.get(entity) {
+                if ship_collider.intersects_wrapped(
+                    ship_transform,
+                    asteroid_collider,
+                    asteroid_transform,
+                    &arena,
+                ) {
+                    commands.entity(entity).try_insert(Hit::Player);
+                    commands.entity(ship_entity).try_insert(Hit::Enemy);
+                }
+            } else if let Ok((bullet_transform, bullet_collider)) = enemy_bullet_query.get(entity) {
+                if ship_collider.intersects_wrapped(
+                    ship_transform,
+                    bullet_collider,
+                    bullet_transform,
+                    &arena,
+                ) {
+                    commands.entity(entity).despawn_recursive();
+                    commands.entity(ship_entity).try_insert(Hit::Enemy);
+                }
             }
         }
     }
@@ -247,21 +440,27 @@ fn destroy(mut commands: Commands, ship_query: Query<&Transform, With<Ship>>) {
 }
 
 fn respawn_timer(
-    mut commands: Commands,
     time: Res<Time>,
-    mut timer_query: Query<(Entity, &mut RespawnTime)>,
-    ship_query: Query<Entity, With<DestroyedShip>>,
+    mut timer_query: Query<&mut RespawnTime>,
     mut next_state: ResMut<NextState<ShipState>>,
 ) {
-    for (entity, mut respawn_timer) in &mut timer_query {
-        respawn_timer.0.tick(time.delta());
-
-        if respawn_timer.0.finished() {
-            commands.entity(entity).despawn();
-            let ship = ship_query.single();
-            commands.entity(ship).despawn_recursive();
-
+    for mut respawn_timer in &mut timer_query {
+        if respawn_timer.0.tick(time.delta()).finished() {
             next_state.set(ShipState::Flying);
         }
     }
 }
+
+// Leaving the state also covers the game ending, or being quit, while the ship is destroyed
+fn despawn_wreck(
+    mut commands: Commands,
+    timer_query: Query<Entity, With<RespawnTime>>,
+    wreck_query: Query<Entity, With<DestroyedShip>>,
+) {
+    for entity in &timer_query {
+        commands.entity(entity).despawn();
+    }
+    for entity in &wreck_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,147 +1,644 @@
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{
+    audio::{PitchBundle, Volume},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     asteroids::{BangLargeEvent, BangMediumEvent, BangSmallEvent},
     bullets::BulletFiredEvent,
-    ship::ThrustEvent,
+    pause::PauseState,
+    saucer::{SaucerSirenEvent, SaucerSize},
+    ship::{HyperspaceEvent, Ship, ShipState, Thrusting},
+    waves::BeatEvent,
+    Arena, GameState,
 };
 
+const THRUST_FADE_OUT: Duration = Duration::from_millis(200);
+// Frequencies of the beat's tones, in hertz
+const LOW_BEAT: f32 = 98.;
+const HIGH_BEAT: f32 = 110.;
+const BEAT_LENGTH: Duration = Duration::from_millis(100);
+// Distance between the listener's ears. Sounds are placed between them, so they are panned without
+// getting quieter.
+const EAR_GAP: f32 = 1.;
+// How loud a sound from as far from the ship as it gets is, with distance attenuation on
+const FARTHEST_VOLUME: f32 = 0.3;
+
 pub struct SoundPlugin;
 
+// Every sound is a registration below: the event that plays it and how it is played
 impl Plugin for SoundPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, load_sounds)
+        app.init_resource::<SoundRegistry>()
             .add_systems(
-                Update,
-                (fire_laser_sound).run_if(on_event::<BulletFiredEvent>()),
+                Startup,
+                (
+                    spawn_listener,
+                    load_sounds,
+                    load_thrust_sound,
+                    make_beat_tones,
+                ),
             )
-            .add_systems(Update, (thrust_sound).run_if(on_event::<ThrustEvent>()))
-            .add_systems(
-                Update,
-                (bang_large_sound).run_if(on_event::<BangLargeEvent>()),
+            .add_systems(Update, thrust_sound)
+            .add_systems(Update, beat_sound.run_if(on_event::<BeatEvent>()))
+            .add_systems(OnEnter(ShipState::Destroyed), stop_thrust_sound)
+            .add_systems(OnEnter(GameState::Menu), stop_thrust_sound)
+            .add_systems(OnEnter(PauseState::Paused), pause_sounds)
+            .add_systems(OnExit(PauseState::Paused), resume_sounds)
+            .add_sound::<BulletFiredEvent>(
+                Sound::new(&["sounds/fire.ogg"])
+                    .with_pitch_variance(0.05)
+                    .with_max_instances(4),
             )
-            .add_systems(
-                Update,
-                (bang_medium_sound).run_if(on_event::<BangMediumEvent>()),
+            .add_sound::<BangLargeEvent>(
+                Sound::new(&["sounds/bangLarge.ogg"]).with_pitch_variance(0.1),
             )
-            .add_systems(
-                Update,
-                (bang_small_sound).run_if(on_event::<BangSmallEvent>()),
+            .add_sound::<BangMediumEvent>(
+                Sound::new(&["sounds/bangMedium.ogg"]).with_pitch_variance(0.1),
+            )
+            .add_sound::<BangSmallEvent>(
+                Sound::new(&["sounds/bangSmall.ogg"]).with_pitch_variance(0.1),
+            )
+            // there is no saucer sample, the siren is the thrust sample played back faster,
+            // higher pitched for the small saucer
+            .add_sound_when::<SaucerSirenEvent>(
+                |siren| siren.size == SaucerSize::Large,
+                Sound::new(&["sounds/thrust.ogg"])
+                    .with_volume(0.6)
+                    .with_speed(1.5)
+                    .with_max_instances(1),
+            )
+            .add_sound_when::<SaucerSirenEvent>(
+                |siren| siren.size == SaucerSize::Small,
+                Sound::new(&["sounds/thrust.ogg"])
+                    .with_volume(0.6)
+                    .with_speed(2.5)
+                    .with_max_instances(1),
+            )
+            // neither is there a hyperspace sample, it is the thrust sample played back slower
+            .add_sound_when::<HyperspaceEvent>(
+                |event| matches!(event, HyperspaceEvent::Jumped(_)),
+                Sound::new(&["sounds/thrust.ogg"]).with_speed(0.5),
             );
     }
 }
 
-#[derive(Resource, Deref)]
-struct FireLaserSound(pub Handle<AudioSource>);
+/// The sounds are grouped into channels, each with its own volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundChannel {
+    /// Every sound
+    Master,
+    Effects,
+    /// The beat
+    Music,
+}
 
-#[derive(Resource, Deref)]
-struct ThrustSound(pub Handle<AudioSource>);
+impl SoundChannel {
+    pub const ALL: [SoundChannel; 3] = [
+        SoundChannel::Master,
+        SoundChannel::Effects,
+        SoundChannel::Music,
+    ];
 
-#[derive(Resource, Deref)]
-struct BangLargeSound(pub Handle<AudioSource>);
+    pub fn label(&self) -> &'static str {
+        match self {
+            SoundChannel::Master => "Master volume",
+            SoundChannel::Effects => "Effects volume",
+            SoundChannel::Music => "Music volume",
+        }
+    }
+}
 
-#[derive(Resource, Deref)]
-struct BangMediumSound(pub Handle<AudioSource>);
+/// How loud each channel is, from 0 to 1
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct VolumeMixer {
+    pub master: f32,
+    pub effects: f32,
+    pub music: f32,
+    pub muted: bool,
+    /// Whether sounds are quieter the farther they are from the ship
+    pub distance_attenuation: bool,
+}
 
-#[derive(Resource, Deref)]
-struct BangSmallSound(pub Handle<AudioSource>);
+impl Default for VolumeMixer {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            effects: 1.,
+            music: 1.,
+            muted: false,
+            distance_attenuation: false,
+        }
+    }
+}
 
-fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let fire_laser_sound_handle = asset_server.load("sounds/fire.ogg");
-    commands.insert_resource(FireLaserSound(fire_laser_sound_handle));
+impl VolumeMixer {
+    /// The channel's own setting
+    pub fn level(&self, channel: SoundChannel) -> f32 {
+        match channel {
+            SoundChannel::Master => self.master,
+            SoundChannel::Effects => self.effects,
+            SoundChannel::Music => self.music,
+        }
+    }
 
-    let thrust_sound_handle = asset_server.load("sounds/thrust.ogg");
-    commands.insert_resource(ThrustSound(thrust_sound_handle));
+    pub fn set_level(&mut self, channel: SoundChannel, level: f32) {
+        let level = level.clamp(0., 1.);
+        match channel {
+            SoundChannel::Master => self.master = level,
+            SoundChannel::Effects => self.effects = level,
+            SoundChannel::Music => self.music = level,
+        }
+    }
 
-    let bang_large_sound_handle = asset_server.load("sounds/bangLarge.ogg");
-    commands.insert_resource(BangLargeSound(bang_large_sound_handle));
-    let bang_medium_sound_handle = asset_server.load("sounds/bangMedium.ogg");
-    commands.insert_resource(BangMediumSound(bang_medium_sound_handle));
-    let bang_small_sound_handle = asset_server.load("sounds/bangSmall.ogg");
-    commands.insert_resource(BangSmallSound(bang_small_sound_handle));
+    /// How loud the channel's sounds are played, taking the master volume and mute into account
+    pub fn volume(&self, channel: SoundChannel) -> f32 {
+        if self.muted {
+            0.
+        } else if channel == SoundChannel::Master {
+            self.master
+        } else {
+            self.master * self.level(channel)
+        }
+    }
 }
 
-fn fire_laser_sound(
-    mut commands: Commands,
-    mut event_reader: EventReader<BulletFiredEvent>,
-    sound: Res<FireLaserSound>,
-) {
-    if !event_reader.is_empty() {
-        event_reader.clear();
-        commands.spawn(AudioBundle {
-            source: sound.clone(),
-            settings: PlaybackSettings::DESPAWN,
-        });
+/// How a sound is played when its event is sent
+#[derive(Debug, Clone)]
+pub(crate) struct Sound {
+    /// One of these is picked at random every time the sound plays
+    pub paths: &'static [&'static str],
+    pub volume: f32,
+    /// Playback speed, which is also the pitch
+    pub speed: f32,
+    /// How far the speed may randomly stray from `speed`, as a fraction of it
+    pub pitch_variance: f32,
+    /// Most instances of the sound playing at once, further events are ignored
+    pub max_instances: usize,
+}
+
+impl Sound {
+    pub fn new(paths: &'static [&'static str]) -> Self {
+        Self {
+            paths,
+            volume: 1.,
+            speed: 1.,
+            pitch_variance: 0.,
+            max_instances: usize::MAX,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_pitch_variance(mut self, pitch_variance: f32) -> Self {
+        self.pitch_variance = pitch_variance;
+        self
+    }
+
+    pub fn with_max_instances(mut self, max_instances: usize) -> Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    /// Whether another instance may start, with `playing` instances of the sound already playing
+    fn has_room(&self, playing: usize) -> bool {
+        playing < self.max_instances
+    }
+
+    /// The speed to play the sound at this time, somewhere within the pitch variance
+    fn pick_speed(&self, rng: &mut impl Rng) -> f32 {
+        let variance = self.speed * self.pitch_variance;
+        self.speed + rng.gen_range(-variance..=variance)
     }
 }
 
-fn thrust_sound(
-    mut commands: Commands,
-    mut event_reader: EventReader<ThrustEvent>,
-    sound: Res<ThrustSound>,
-) {
-    if !event_reader.is_empty() {
-        event_reader.clear();
-        commands.spawn(AudioBundle {
-            source: sound.clone(),
-            settings: PlaybackSettings::DESPAWN,
+// Out of a frame's events, the one a sound registered with the condition is played for
+fn last_matching<'a, E>(
+    events: impl Iterator<Item = &'a E>,
+    condition: fn(&E) -> bool,
+) -> Option<&'a E> {
+    events.filter(|event| condition(event)).last()
+}
+
+/// An event that can play a sound
+pub(crate) trait SoundEvent: Event {
+    /// Where in the arena the sound comes from, it is heard from the middle without one
+    fn position(&self) -> Option<Vec2> {
+        None
+    }
+}
+
+impl SoundEvent for BulletFiredEvent {
+    fn position(&self) -> Option<Vec2> {
+        Some(self.position)
+    }
+}
+
+impl SoundEvent for BangLargeEvent {
+    fn position(&self) -> Option<Vec2> {
+        Some(self.0)
+    }
+}
+
+impl SoundEvent for BangMediumEvent {
+    fn position(&self) -> Option<Vec2> {
+        Some(self.0)
+    }
+}
+
+impl SoundEvent for BangSmallEvent {
+    fn position(&self) -> Option<Vec2> {
+        Some(self.0)
+    }
+}
+
+impl SoundEvent for SaucerSirenEvent {
+    fn position(&self) -> Option<Vec2> {
+        Some(self.position)
+    }
+}
+
+impl SoundEvent for HyperspaceEvent {
+    fn position(&self) -> Option<Vec2> {
+        match self {
+            HyperspaceEvent::Jumped(position)
+            | HyperspaceEvent::Reappeared(position)
+            | HyperspaceEvent::Exploded(position) => Some(*position),
+        }
+    }
+}
+
+pub(crate) trait AddSound {
+    /// Plays the sound whenever `E` is sent
+    fn add_sound<E: SoundEvent>(&mut self, sound: Sound) -> &mut Self;
+
+    /// Plays the sound whenever an `E` the condition holds for is sent
+    fn add_sound_when<E: SoundEvent>(
+        &mut self,
+        condition: fn(&E) -> bool,
+        sound: Sound,
+    ) -> &mut Self;
+}
+
+impl AddSound for App {
+    fn add_sound<E: SoundEvent>(&mut self, sound: Sound) -> &mut Self {
+        self.add_sound_when::<E>(|_| true, sound)
+    }
+
+    fn add_sound_when<E: SoundEvent>(
+        &mut self,
+        condition: fn(&E) -> bool,
+        sound: Sound,
+    ) -> &mut Self {
+        let mut registry = self
+            .world_mut()
+            .get_resource_or_insert_with(SoundRegistry::default);
+        let index = registry.0.len();
+        registry.0.push(RegisteredSound {
+            sound,
+            handles: Vec::new(),
         });
+
+        self.add_systems(
+            Update,
+            (move |commands: Commands,
+                   events: EventReader<E>,
+                   registry: Res<SoundRegistry>,
+                   mixing: Mixing,
+                   playing_query: Query<&PlayingSound>| {
+                play_sound(
+                    index,
+                    condition,
+                    commands,
+                    events,
+                    registry,
+                    mixing,
+                    playing_query,
+                )
+            })
+            .run_if(on_event::<E>()),
+        )
+    }
+}
+
+struct RegisteredSound {
+    sound: Sound,
+    handles: Vec<Handle<AudioSource>>,
+}
+
+#[derive(Resource, Default)]
+struct SoundRegistry(Vec<RegisteredSound>);
+
+// Which registered sound an audio entity is playing
+#[derive(Component)]
+struct PlayingSound(usize);
+
+fn load_sounds(asset_server: Res<AssetServer>, mut registry: ResMut<SoundRegistry>) {
+    for registered in &mut registry.0 {
+        registered.handles = registered
+            .sound
+            .paths
+            .iter()
+            .map(|&path| asset_server.load(path))
+            .collect();
     }
 }
 
-fn bang_large_sound(
+// How loud a sound is and where it is heard from, going by where it came from
+#[derive(SystemParam)]
+struct Mixing<'w, 's> {
+    mixer: Res<'w, VolumeMixer>,
+    arena: Res<'w, Arena>,
+    ship_query: Query<'w, 's, &'static Transform, With<Ship>>,
+}
+
+impl Mixing<'_, '_> {
+    fn volume(&self, channel: SoundChannel, position: Option<Vec2>) -> f32 {
+        let volume = self.mixer.volume(channel);
+        if !self.mixer.distance_attenuation {
+            return volume;
+        }
+
+        let (Some(position), Ok(ship_transform)) = (position, self.ship_query.get_single()) else {
+            return volume;
+        };
+        volume * attenuation(position, ship_transform.translation.truncate(), &self.arena)
+    }
+
+    fn emitter(&self, position: Vec2) -> Transform {
+        Transform::from_xyz(pan(position, &self.arena) * EAR_GAP / 2., 0., 0.)
+    }
+}
+
+// From -1 at the left edge of the arena to 1 at the right edge: left of the middle is towards the
+// left ear, right of it towards the right one
+fn pan(position: Vec2, arena: &Arena) -> f32 {
+    (position.x / (arena.width / 2.)).clamp(-1., 1.)
+}
+
+// How much quieter a sound is at the position than at the ship. Distance is measured the short
+// way round, across the edges of the arena.
+fn attenuation(position: Vec2, ship: Vec2, arena: &Arena) -> f32 {
+    let farthest = Vec2::new(arena.width, arena.height).length() / 2.;
+    let distance = arena.wrap(position - ship).length();
+    1. - (1. - FARTHEST_VOLUME) * (distance / farthest).min(1.)
+}
+
+// However many events were sent, the sound plays once a frame, from where the last one came from
+fn play_sound<E: SoundEvent>(
+    index: usize,
+    condition: fn(&E) -> bool,
     mut commands: Commands,
-    mut event_reader: EventReader<BangLargeEvent>,
-    sound: Res<BangLargeSound>,
+    mut events: EventReader<E>,
+    registry: Res<SoundRegistry>,
+    mixing: Mixing,
+    playing_query: Query<&PlayingSound>,
 ) {
-    if !event_reader.is_empty() {
-        event_reader.clear();
-        commands.spawn(AudioBundle {
-            source: sound.clone(),
-            settings: PlaybackSettings::DESPAWN,
-        });
+    let Some(event) = last_matching(events.read(), condition) else {
+        return;
+    };
+    let position = event.position();
+
+    let RegisteredSound { sound, handles } = &registry.0[index];
+    let playing = playing_query
+        .iter()
+        .filter(|playing| playing.0 == index)
+        .count();
+    if !sound.has_room(playing) {
+        return;
     }
+
+    // not the game's random numbers, sounds don't get played back in replays
+    let mut rng = rand::thread_rng();
+    let Some(source) = handles.choose(&mut rng) else {
+        return;
+    };
+    let speed = sound.pick_speed(&mut rng);
+
+    let settings = PlaybackSettings::DESPAWN
+        .with_volume(Volume::new(
+            sound.volume * mixing.volume(SoundChannel::Effects, position),
+        ))
+        .with_speed(speed);
+    let mut audio = commands.spawn((
+        AudioBundle {
+            source: source.clone(),
+            settings,
+        },
+        PlayingSound(index),
+    ));
+    if let Some(position) = position {
+        let emitter = mixing.emitter(position);
+        audio.insert((
+            settings.with_spatial(true),
+            TransformBundle {
+                local: emitter,
+                global: emitter.into(),
+            },
+        ));
+    }
+}
+
+fn spawn_listener(mut commands: Commands) {
+    commands.spawn((SpatialListener::new(EAR_GAP), TransformBundle::default()));
 }
-fn bang_medium_sound(
+
+#[derive(Resource, Deref)]
+struct ThrustSample(Handle<AudioSource>);
+
+// The engine sound, looping for as long as the ship thrusts
+#[derive(Component)]
+struct ThrustSound;
+
+fn load_thrust_sound(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThrustSample(asset_server.load("sounds/thrust.ogg")));
+}
+
+fn thrust_sound(
     mut commands: Commands,
-    mut event_reader: EventReader<BangMediumEvent>,
-    sound: Res<BangMediumSound>,
+    time: Res<Time>,
+    mixer: Res<VolumeMixer>,
+    sample: Res<ThrustSample>,
+    ship_query: Query<&Thrusting, With<Ship>>,
+    sound_query: Query<(Entity, Option<&AudioSink>), With<ThrustSound>>,
 ) {
-    if !event_reader.is_empty() {
-        event_reader.clear();
-        commands.spawn(AudioBundle {
-            source: sound.clone(),
-            settings: PlaybackSettings::DESPAWN,
-        });
+    let thrusting = ship_query.get_single().is_ok_and(|thrusting| thrusting.0);
+
+    let Ok((sound, sink)) = sound_query.get_single() else {
+        if thrusting {
+            commands.spawn((
+                AudioBundle {
+                    source: sample.clone(),
+                    settings: PlaybackSettings::LOOP
+                        .with_volume(Volume::new(mixer.volume(SoundChannel::Effects))),
+                },
+                ThrustSound,
+            ));
+        }
+        return;
+    };
+
+    // the sink shows up once the sample has loaded
+    let Some(sink) = sink else {
+        if !thrusting {
+            commands.entity(sound).despawn();
+        }
+        return;
+    };
+
+    let full_volume = mixer.volume(SoundChannel::Effects);
+    if thrusting {
+        sink.set_volume(full_volume);
+    } else {
+        let volume =
+            sink.volume() - full_volume * time.delta_seconds() / THRUST_FADE_OUT.as_secs_f32();
+        if volume > 0. {
+            sink.set_volume(volume);
+        } else {
+            commands.entity(sound).despawn();
+        }
     }
 }
-fn bang_small_sound(
+
+fn stop_thrust_sound(mut commands: Commands, sound_query: Query<Entity, With<ThrustSound>>) {
+    for sound in &sound_query {
+        commands.entity(sound).despawn();
+    }
+}
+
+// There are no beat samples either, the beat is made of plain tones
+#[derive(Resource)]
+struct BeatTones {
+    low: Handle<Pitch>,
+    high: Handle<Pitch>,
+}
+
+fn make_beat_tones(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    commands.insert_resource(BeatTones {
+        low: pitches.add(Pitch::new(LOW_BEAT, BEAT_LENGTH)),
+        high: pitches.add(Pitch::new(HIGH_BEAT, BEAT_LENGTH)),
+    });
+}
+
+fn beat_sound(
     mut commands: Commands,
-    mut event_reader: EventReader<BangSmallEvent>,
-    sound: Res<BangSmallSound>,
+    mut beat_events: EventReader<BeatEvent>,
+    tones: Res<BeatTones>,
+    mixer: Res<VolumeMixer>,
 ) {
-    if !event_reader.is_empty() {
-        event_reader.clear();
-        commands.spawn(AudioBundle {
-            source: sound.clone(),
-            settings: PlaybackSettings::DESPAWN,
+    if let Some(beat) = beat_events.read().last() {
+        let tone = match beat {
+            BeatEvent::Low => &tones.low,
+            BeatEvent::High => &tones.high,
+        };
+        commands.spawn(PitchBundle {
+            source: tone.clone(),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(mixer.volume(SoundChannel::Music))),
         });
     }
 }
 
-// fn play_sound_on_event<E: Event, S: Resource>(
-//     mut commands: Commands,
-//     mut event_reader: EventReader<E>,
-//     sound: Res<S>,
-// ) {
-//     if !event_reader.is_empty() {
-//         event_reader.clear();
+fn pause_sounds(sink_query: Query<&AudioSink>, spatial_sink_query: Query<&SpatialAudioSink>) {
+    for sink in &sink_query {
+        sink.pause();
+    }
+    for sink in &spatial_sink_query {
+        sink.pause();
+    }
+}
+
+fn resume_sounds(sink_query: Query<&AudioSink>, spatial_sink_query: Query<&SpatialAudioSink>) {
+    for sink in &sink_query {
+        sink.play();
+    }
+    for sink in &spatial_sink_query {
+        sink.play();
+    }
+}
 
-//         let sound: Handle<AudioSource> = sound;
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
 
-//         commands.spawn(AudioBundle {
-//             source: sound.clone(),
-//             settings: PlaybackSettings::DESPAWN,
-//         });
-//     }
-// }
+    use super::*;
+
+    #[test]
+    fn sounds_stop_starting_at_their_instance_cap() {
+        let sound = Sound::new(&[]).with_max_instances(2);
+
+        assert!(sound.has_room(0));
+        assert!(sound.has_room(1));
+        assert!(!sound.has_room(2));
+        assert!(Sound::new(&[]).has_room(100));
+    }
+
+    #[test]
+    fn sounds_play_for_the_last_event_their_condition_holds_for() {
+        let sirens = [
+            (SaucerSize::Large, 0),
+            (SaucerSize::Small, 1),
+            (SaucerSize::Large, 2),
+        ];
+        let large: fn(&(SaucerSize, i32)) -> bool = |siren| siren.0 == SaucerSize::Large;
+        let small: fn(&(SaucerSize, i32)) -> bool = |siren| siren.0 == SaucerSize::Small;
+
+        assert_eq!(last_matching(sirens.iter(), large), Some(&sirens[2]));
+        assert_eq!(last_matching(sirens.iter(), small), Some(&sirens[1]));
+        assert_eq!(last_matching(sirens[..1].iter(), small), None);
+    }
+
+    const ARENA: Arena = Arena {
+        width: 800.,
+        height: 600.,
+    };
+
+    #[test]
+    fn sounds_pan_from_edge_to_edge() {
+        assert_eq!(pan(Vec2::new(-400., 100.), &ARENA), -1.);
+        assert_eq!(pan(Vec2::new(0., -200.), &ARENA), 0.);
+        assert_eq!(pan(Vec2::new(400., 0.), &ARENA), 1.);
+        assert_eq!(pan(Vec2::new(200., 0.), &ARENA), 0.5);
+    }
+
+    #[test]
+    fn attenuation_measures_across_the_edges() {
+        let ship = Vec2::new(390., 0.);
+
+        assert_eq!(attenuation(ship, ship, &ARENA), 1.);
+        // 20 apart across the right edge, not 780 apart through the middle
+        let across = attenuation(Vec2::new(-390., 0.), ship, &ARENA);
+        let near = attenuation(Vec2::new(370., 0.), ship, &ARENA);
+        assert!((across - near).abs() < 1e-6, "{across} != {near}");
+        assert!(across > 0.95);
+        // as far as it gets, half the arena away both ways
+        let farthest = attenuation(Vec2::new(-10., 300.), ship, &ARENA);
+        assert!((farthest - FARTHEST_VOLUME).abs() < 1e-6, "{farthest}");
+    }
+
+    #[test]
+    fn speed_strays_no_further_than_the_pitch_variance() {
+        let sound = Sound::new(&[]).with_speed(2.).with_pitch_variance(0.1);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let speeds: Vec<f32> = (0..100).map(|_| sound.pick_speed(&mut rng)).collect();
+
+        assert!(speeds.iter().all(|speed| (1.8..=2.2).contains(speed)));
+        assert!(speeds.iter().any(|&speed| speed != 2.));
+        assert_eq!(Sound::new(&[]).pick_speed(&mut rng), 1.);
+    }
+}
@@ -1,6 +1,12 @@
 use bevy::prelude::*;
 
-use crate::{player::Player, GameState, TEXT_COLOR, TEXT_SIZE};
+use crate::{
+    asteroids::AsteroidsState, player::Player, powerups::ActivePowerUps, shield::Shield,
+    waves::Wave, GameState, TEXT_COLOR, TEXT_SIZE,
+};
+
+// Width of the shield energy bar, in characters
+const SHIELD_BAR_LENGTH: usize = 10;
 
 pub struct UiPlugin;
 
@@ -8,23 +14,80 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             OnEnter(GameState::Playing),
-            (spawn_lifes_ui, spawn_score_ui),
+            (spawn_status_ui, spawn_score_ui, spawn_power_ups_ui),
         )
         .add_systems(
             Update,
-            (update_score_ui, update_lifes_ui).run_if(in_state(GameState::Playing)),
+            (
+                update_score_ui,
+                update_lifes_ui,
+                update_shield_ui,
+                update_power_ups_ui,
+            )
+                .run_if(in_state(GameState::Playing)),
         )
-        .add_systems(OnExit(GameState::Menu), despawn_ui);
+        .add_systems(OnExit(GameState::Menu), despawn_ui)
+        .add_systems(OnExit(GameState::GameOver), despawn_ui)
+        .add_systems(OnEnter(AsteroidsState::Destroyed), spawn_wave_banner)
+        .add_systems(OnExit(AsteroidsState::Destroyed), despawn_wave_banner);
     }
 }
 
+// The row with the lives and the shield
+#[derive(Component)]
+pub struct StatusUI;
+
 #[derive(Component)]
 pub struct LifesUI;
 
+#[derive(Component)]
+pub struct ShieldUI;
+
 #[derive(Component)]
 pub struct ScoreUI;
 
-pub fn spawn_lifes_ui(mut commands: Commands) {
+#[derive(Component)]
+pub struct PowerUpsUI;
+
+#[derive(Component)]
+pub struct WaveBanner;
+
+// The lives, with the shield right of them however many digits they take
+pub fn spawn_status_ui(mut commands: Commands) {
+    let text_style = TextStyle {
+        font_size: TEXT_SIZE,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(30.),
+                    left: Val::Px(0.),
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(TEXT_SIZE / 2.),
+                    ..default()
+                },
+                ..default()
+            },
+            StatusUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([TextSection::from_style(text_style.clone())]),
+                LifesUI,
+            ));
+            parent.spawn((
+                TextBundle::from_sections([TextSection::from_style(text_style)]),
+                ShieldUI,
+            ));
+        });
+}
+
+pub fn spawn_score_ui(mut commands: Commands) {
     commands.spawn((
         TextBundle::from_sections([TextSection::from_style(TextStyle {
             font_size: TEXT_SIZE,
@@ -33,15 +96,15 @@ pub fn spawn_lifes_ui(mut commands: Commands) {
         })])
         .with_style(Style {
             position_type: PositionType::Absolute,
-            top: Val::Px(30.),
+            top: Val::Px(0.),
             left: Val::Px(0.),
             ..default()
         }),
-        LifesUI,
+        ScoreUI,
     ));
 }
 
-pub fn spawn_score_ui(mut commands: Commands) {
+pub fn spawn_power_ups_ui(mut commands: Commands) {
     commands.spawn((
         TextBundle::from_sections([TextSection::from_style(TextStyle {
             font_size: TEXT_SIZE,
@@ -51,22 +114,60 @@ pub fn spawn_score_ui(mut commands: Commands) {
         .with_style(Style {
             position_type: PositionType::Absolute,
             top: Val::Px(0.),
-            left: Val::Px(0.),
+            right: Val::Px(0.),
             ..default()
         }),
-        ScoreUI,
+        PowerUpsUI,
     ));
 }
 
+// Announces the next wave in the middle of the screen while the arena is empty
+pub fn spawn_wave_banner(mut commands: Commands, wave: Res<Wave>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            WaveBanner,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Wave {}", wave.0),
+                TextStyle {
+                    font_size: TEXT_SIZE * 2.,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_wave_banner(mut commands: Commands, banner_query: Query<Entity, With<WaveBanner>>) {
+    for entity in &banner_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 fn despawn_ui(
     mut commands: Commands,
     score_ui_query: Query<Entity, With<ScoreUI>>,
-    lifes_ui_query: Query<Entity, With<LifesUI>>,
+    status_ui_query: Query<Entity, With<StatusUI>>,
+    power_ups_ui_query: Query<Entity, With<PowerUpsUI>>,
 ) {
     score_ui_query
         .iter()
         .for_each(|entity| commands.entity(entity).despawn_recursive());
-    lifes_ui_query
+    status_ui_query
+        .iter()
+        .for_each(|entity| commands.entity(entity).despawn_recursive());
+    power_ups_ui_query
         .iter()
         .for_each(|entity| commands.entity(entity).despawn_recursive());
 }
@@ -82,6 +183,23 @@ pub fn update_lifes_ui(
     }
 }
 
+// Keeps showing the last ship's shield while it is destroyed
+pub fn update_shield_ui(
+    shield_query: Query<&Shield>,
+    mut shield_ui_query: Query<&mut Text, With<ShieldUI>>,
+) {
+    if let Ok(shield) = shield_query.get_single() {
+        if let Ok(mut text) = shield_ui_query.get_single_mut() {
+            let filled = (shield.charge() * SHIELD_BAR_LENGTH as f32).ceil() as usize;
+            text.sections[0].value = format!(
+                "[{}{}]",
+                "|".repeat(filled),
+                " ".repeat(SHIELD_BAR_LENGTH - filled)
+            );
+        }
+    }
+}
+
 pub fn update_score_ui(
     player: Query<&Player>,
     mut score_ui_query: Query<&mut Text, With<ScoreUI>>,
@@ -92,3 +210,19 @@ pub fn update_score_ui(
         }
     }
 }
+
+// One line per active power-up with the seconds it has left
+pub fn update_power_ups_ui(
+    power_ups: Res<ActivePowerUps>,
+    mut power_ups_ui_query: Query<&mut Text, With<PowerUpsUI>>,
+) {
+    if let Ok(mut text) = power_ups_ui_query.get_single_mut() {
+        text.sections[0].value = power_ups
+            .iter()
+            .map(|(power_up, remaining)| {
+                format!("{} {}", power_up.label(), remaining.as_secs_f32().ceil())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use bevy::{prelude::*, time::Stopwatch};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroids::{Asteroid, AsteroidsState},
+    GameState,
+};
+
+const SLOWEST_BEAT: Duration = Duration::from_millis(1000);
+const FASTEST_BEAT: Duration = Duration::from_millis(250);
+// Time into a wave for the beat to speed up all the way, however many asteroids are left
+const BEAT_SPEED_UP_TIME: Duration = Duration::from_secs(90);
+
+pub struct WavesPlugin {
+    pub difficulty: DifficultyCurve,
+}
+
+// A game is played in waves. Every time the last asteroid is destroyed the next wave starts, with
+// more and faster asteroids as laid out by the `DifficultyCurve`. A beat plays along, faster the
+// longer the wave lasts and the fewer asteroids are left.
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wave>()
+            .init_resource::<WaveReached>()
+            .init_resource::<Heartbeat>()
+            .insert_resource(self.difficulty.clone())
+            .add_systems(OnEnter(GameState::Playing), reset_wave)
+            // the menu's asteroids are wave 1's, whatever wave the last game ended in
+            .add_systems(OnEnter(GameState::Menu), reset_wave)
+            .add_systems(OnEnter(AsteroidsState::Destroyed), next_wave)
+            .add_systems(
+                OnEnter(AsteroidsState::Flying),
+                (reset_heartbeat, reach_wave),
+            )
+            .add_systems(
+                FixedUpdate,
+                heartbeat.run_if(in_state(AsteroidsState::Flying)),
+            )
+            .add_event::<BeatEvent>();
+    }
+}
+
+/// The two alternating tones of the beat
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BeatEvent {
+    Low,
+    High,
+}
+
+/// The beat of the wave being played
+#[derive(Resource, Debug)]
+pub struct Heartbeat {
+    wave_time: Stopwatch,
+    until_beat: Timer,
+    next: BeatEvent,
+    most_asteroids: usize,
+    interval: Duration,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self {
+            wave_time: Stopwatch::new(),
+            until_beat: Timer::new(SLOWEST_BEAT, TimerMode::Once),
+            next: BeatEvent::Low,
+            most_asteroids: 0,
+            interval: SLOWEST_BEAT,
+        }
+    }
+}
+
+impl Heartbeat {
+    /// Time between beats at the moment
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+/// The wave being played, or coming up while `AsteroidsState::Destroyed`. Starts at 1.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wave(pub u32);
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// The last wave whose asteroids arrived, in the game being played or the one that just ended.
+/// Unlike `Wave`, it doesn't count a wave the game ended before.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveReached(pub u32);
+
+impl Default for WaveReached {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// How a value grows from wave to wave: `first` in wave 1, then `per_wave` more for every wave
+/// after it, up to `max`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ramp {
+    pub first: f32,
+    pub per_wave: f32,
+    pub max: f32,
+}
+
+impl Ramp {
+    pub fn at(&self, wave: Wave) -> f32 {
+        (self.first + self.per_wave * wave.0.saturating_sub(1) as f32).min(self.max)
+    }
+}
+
+/// How hard each wave is. Read from a RON file, so it can be tuned without building the game again.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DifficultyCurve {
+    /// Number of large asteroids a wave starts with
+    pub asteroids: Ramp,
+    /// Speed of those asteroids, in units per second
+    pub asteroid_speed: Ramp,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            asteroids: Ramp {
+                first: 4.,
+                per_wave: 2.,
+                max: 12.,
+            },
+            asteroid_speed: Ramp {
+                first: 64.,
+                per_wave: 8.,
+                max: 160.,
+            },
+        }
+    }
+}
+
+impl DifficultyCurve {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn asteroid_count(&self, wave: Wave) -> u32 {
+        self.asteroids.at(wave).round() as u32
+    }
+
+    pub fn asteroid_speed(&self, wave: Wave) -> f32 {
+        self.asteroid_speed.at(wave)
+    }
+}
+
+pub(crate) fn reset_wave(mut wave: ResMut<Wave>) {
+    *wave = Wave::default();
+}
+
+fn next_wave(mut wave: ResMut<Wave>) {
+    wave.0 += 1;
+    info!("Wave {} coming up", wave.0);
+}
+
+fn reach_wave(wave: Res<Wave>, mut reached: ResMut<WaveReached>) {
+    reached.0 = wave.0;
+}
+
+fn reset_heartbeat(mut heartbeat: ResMut<Heartbeat>) {
+    *heartbeat = Heartbeat::default();
+}
+
+fn heartbeat(
+    time: Res<Time>,
+    mut heartbeat: ResMut<Heartbeat>,
+    asteroid_query: Query<(), With<Asteroid>>,
+    mut beat_event: EventWriter<BeatEvent>,
+) {
+    // the asteroids of a new wave only turn up after it starts, and split into more along the way
+    let asteroids = asteroid_query.iter().count();
+    heartbeat.most_asteroids = heartbeat.most_asteroids.max(asteroids);
+    heartbeat.wave_time.tick(time.delta());
+
+    let time_factor =
+        heartbeat.wave_time.elapsed().as_secs_f32() / BEAT_SPEED_UP_TIME.as_secs_f32();
+    let asteroid_factor = match heartbeat.most_asteroids {
+        0 => 0.,
+        most => 1. - asteroids as f32 / most as f32,
+    };
+    let speed_up = (time_factor + asteroid_factor).min(1.);
+    heartbeat.interval = SLOWEST_BEAT.mul_f32(1. - speed_up) + FASTEST_BEAT.mul_f32(speed_up);
+
+    if heartbeat.until_beat.tick(time.delta()).finished() {
+        let beat = heartbeat.next;
+        beat_event.send(beat);
+        heartbeat.next = match beat {
+            BeatEvent::Low => BeatEvent::High,
+            BeatEvent::High => BeatEvent::Low,
+        };
+        heartbeat.until_beat = Timer::new(heartbeat.interval, TimerMode::Once);
+    }
+}
@@ -0,0 +1,123 @@
+use asteroids::{AsteroidSize, Collider, ShipState};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn bullet_splits_large_asteroid_into_two_medium_ones() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 0.), AsteroidSize::Large);
+    spawn_bullet(&mut app, Vec2::new(200., 0.), Vec2::Y);
+
+    advance(&mut app, 3);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 0);
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Medium), 2);
+    assert_eq!(player(&mut app).score, 20);
+}
+
+#[test]
+fn bullet_destroys_small_asteroid() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(-200., 100.), AsteroidSize::Small);
+    spawn_bullet(&mut app, Vec2::new(-200., 100.), Vec2::X);
+
+    advance(&mut app, 3);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Small), 0);
+    assert_eq!(player(&mut app).score, 100);
+}
+
+#[test]
+fn bullet_misses_distant_asteroid() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 0.), AsteroidSize::Large);
+    spawn_bullet(&mut app, Vec2::new(-200., 0.), Vec2::NEG_X);
+
+    advance(&mut app, 3);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 1);
+    assert_eq!(player(&mut app).score, 0);
+}
+
+#[test]
+fn asteroid_collision_destroys_ship_and_costs_a_life() {
+    let mut app = new_game();
+    place_ship(&mut app, Vec2::new(100., 100.));
+    spawn_asteroid(&mut app, Vec2::new(100., 100.), AsteroidSize::Medium);
+
+    advance(&mut app, 3);
+
+    assert_eq!(player(&mut app).life_count, 2);
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+}
+
+#[test]
+fn bullet_hits_large_asteroid_near_its_edge() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 0.), AsteroidSize::Large);
+    spawn_bullet(&mut app, Vec2::new(200., -36.), Vec2::NEG_X);
+
+    advance(&mut app, 3);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 0);
+}
+
+#[test]
+fn bullet_passes_beside_small_asteroid() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 0.), AsteroidSize::Small);
+    spawn_bullet(&mut app, Vec2::new(150., 14.), Vec2::X);
+
+    advance(&mut app, 10);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Small), 1);
+}
+
+fn at(x: f32, y: f32) -> Transform {
+    Transform::from_xyz(x, y, 0.)
+}
+
+#[test]
+fn circles_touching_intersect() {
+    let circle = Collider::Circle(10.);
+
+    assert!(circle.intersects(&at(0., 0.), &Collider::Circle(5.), &at(15., 0.)));
+    assert!(!circle.intersects(&at(0., 0.), &Collider::Circle(5.), &at(15.1, 0.)));
+}
+
+#[test]
+fn triangle_collides_with_its_drawn_shape() {
+    let ship = Collider::Triangle(15.);
+    let bullet = Collider::Circle(2.);
+
+    // the nose points along +Y and reaches out to the radius
+    assert!(ship.intersects(&at(0., 0.), &bullet, &at(0., 16.)));
+    // the flat back is only half the radius behind the center
+    assert!(!ship.intersects(&at(0., 0.), &bullet, &at(0., -10.)));
+    assert!(ship.intersects(&at(0., 0.), &bullet, &at(0., -9.)));
+    // a circle centered inside the triangle
+    assert!(ship.intersects(&at(0., 0.), &Collider::Circle(1.), &at(0., 0.)));
+    // order of the colliders doesn't matter
+    assert!(bullet.intersects(&at(0., 16.), &ship, &at(0., 0.)));
+}
+
+#[test]
+fn triangle_rotates_with_its_transform() {
+    let ship = Collider::Triangle(15.);
+    let bullet = Collider::Circle(2.);
+    let turned_around = at(0., 0.).with_rotation(Quat::from_rotation_z(std::f32::consts::PI));
+
+    assert!(ship.intersects(&turned_around, &bullet, &at(0., -16.)));
+    assert!(!ship.intersects(&turned_around, &bullet, &at(0., 16.)));
+}
+
+#[test]
+fn triangles_intersect_when_overlapping() {
+    let ship = Collider::Triangle(15.);
+
+    assert!(ship.intersects(&at(0., 0.), &ship, &at(10., 0.)));
+    assert!(!ship.intersects(&at(0., 0.), &ship, &at(0., 25.)));
+}
@@ -0,0 +1,231 @@
+// Not every test binary uses every helper
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use asteroids::{
+    Arena, Asteroid, AsteroidBundle, AsteroidSize, Asteroids, Bullet, BulletBundle, GameState,
+    Heading, Player, Position, Saucer, SaucerBundle, SaucerSize, Ship, ShipState, Velocity,
+};
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+
+pub const ARENA: Arena = Arena {
+    width: 800.,
+    height: 600.,
+};
+
+/// Builds a headless game that has just entered `GameState::Playing`. The ship sits at the origin
+/// and the randomly spawned asteroids are already cleared away, no fixed tick has run yet.
+pub fn new_game() -> App {
+    new_game_with(Asteroids::headless(ARENA).with_seed(0))
+}
+
+pub fn new_game_with(asteroids: Asteroids) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(asteroids)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+
+    app.update();
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+
+    clear_asteroids(&mut app);
+
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+
+    app
+}
+
+pub fn clear_asteroids(app: &mut App) {
+    let asteroids: Vec<Entity> = app
+        .world_mut()
+        .query_filtered::<Entity, With<Asteroid>>()
+        .iter(app.world())
+        .collect();
+    for asteroid in asteroids {
+        app.world_mut().entity_mut(asteroid).despawn_recursive();
+    }
+}
+
+/// Runs frames until exactly `ticks` more fixed ticks have passed
+pub fn advance(app: &mut App, ticks: u32) {
+    let target = fixed_ticks(app) + ticks;
+    while fixed_ticks(app) < target {
+        app.update();
+    }
+}
+
+fn fixed_ticks(app: &App) -> u32 {
+    let time = app.world().resource::<Time<Fixed>>();
+    (time.elapsed().as_nanos() / time.timestep().as_nanos()) as u32
+}
+
+/// Spawns a motionless asteroid
+pub fn spawn_asteroid(app: &mut App, position: Vec2, size: AsteroidSize) -> Entity {
+    spawn_moving_asteroid(app, position, Vec2::ZERO, size)
+}
+
+pub fn spawn_moving_asteroid(
+    app: &mut App,
+    position: Vec2,
+    velocity: Vec2,
+    size: AsteroidSize,
+) -> Entity {
+    app.world_mut()
+        .spawn(AsteroidBundle::new(
+            Position(position),
+            Velocity(velocity.extend(0.)),
+            size,
+        ))
+        .id()
+}
+
+pub fn spawn_bullet(app: &mut App, position: Vec2, heading: Vec2) -> Entity {
+    app.world_mut()
+        .spawn(BulletBundle::new(
+            Heading(heading.normalize().extend(0.)),
+            Position(position),
+        ))
+        .id()
+}
+
+pub fn spawn_saucer(app: &mut App, position: Vec2, direction: f32, size: SaucerSize) -> Entity {
+    app.world_mut()
+        .spawn(SaucerBundle::new(Position(position), direction, size))
+        .id()
+}
+
+pub fn saucer_count(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<Saucer>>()
+        .iter(app.world())
+        .count()
+}
+
+pub fn place_ship(app: &mut App, position: Vec2) -> Entity {
+    let mut ships = app.world_mut().query_filtered::<Entity, With<Ship>>();
+    let ship = ships.single(app.world());
+
+    app.world_mut()
+        .get_mut::<Transform>(ship)
+        .unwrap()
+        .translation = position.extend(0.);
+
+    ship
+}
+
+pub fn asteroid_count(app: &mut App, size: AsteroidSize) -> usize {
+    app.world_mut()
+        .query::<&AsteroidSize>()
+        .iter(app.world())
+        .filter(|&&asteroid_size| asteroid_size == size)
+        .count()
+}
+
+pub fn player(app: &mut App) -> &Player {
+    app.world_mut().query::<&Player>().single(app.world())
+}
+
+pub fn ship_state(app: &App) -> ShipState {
+    app.world().resource::<State<ShipState>>().get().clone()
+}
+
+/// Taps the fire button
+pub fn fire(app: &mut App) {
+    tap(app, KeyCode::Space, Key::Space);
+}
+
+/// Presses the key for one frame and releases it in the next
+pub fn tap(app: &mut App, key_code: KeyCode, logical_key: Key) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key: logical_key.clone(),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+}
+
+pub fn bullet_translations(app: &mut App) -> Vec<Vec3> {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Bullet>>()
+        .iter(app.world())
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+/// Changes how many fixed ticks run per second of game time
+pub fn set_fixed_rate(app: &mut App, hz: f64) {
+    app.world_mut()
+        .resource_mut::<Time<Fixed>>()
+        .set_timestep_hz(hz);
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+}
+
+pub fn ship_transform(app: &mut App) -> Transform {
+    *app.world_mut()
+        .query_filtered::<&Transform, With<Ship>>()
+        .single(app.world())
+}
+
+/// A file path in a directory of its own under the system's temp directory. The directory and
+/// whatever was written to it are removed again when this is dropped.
+pub struct ScratchFile(PathBuf);
+
+pub fn scratch_file(name: &str) -> ScratchFile {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "asteroids-test-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    ScratchFile(dir.join(name))
+}
+
+impl std::ops::Deref for ScratchFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ScratchFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<&ScratchFile> for PathBuf {
+    fn from(file: &ScratchFile) -> Self {
+        file.0.clone()
+    }
+}
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        if let Some(dir) = self.0.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
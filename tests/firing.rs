@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use asteroids::{Asteroids, BulletSettings, Lifetime, Settings};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+fn hold_fire(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+}
+
+#[test]
+fn holding_fire_keeps_firing() {
+    let mut app = new_game();
+
+    hold_fire(&mut app);
+    advance(&mut app, 40);
+
+    // a shot every quarter of a second, at 64 ticks a second
+    assert_eq!(bullet_translations(&mut app).len(), 3);
+}
+
+#[test]
+fn mashing_fire_is_no_faster_than_holding_it() {
+    let mut app = new_game();
+
+    for _ in 0..5 {
+        fire(&mut app);
+    }
+
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+}
+
+#[test]
+fn taps_during_the_cooldown_are_not_fired_later() {
+    let mut app = new_game();
+
+    fire(&mut app);
+    fire(&mut app);
+    advance(&mut app, 32);
+
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+}
+
+#[test]
+fn no_more_than_four_bullets_at_once() {
+    let mut app = new_game();
+
+    hold_fire(&mut app);
+    advance(&mut app, 96);
+
+    assert_eq!(bullet_translations(&mut app).len(), 4);
+}
+
+#[test]
+fn fire_rate_and_bullet_cap_are_configurable() {
+    let mut app = new_game();
+    {
+        let mut settings = app.world_mut().resource_mut::<BulletSettings>();
+        settings.fire_interval = Duration::from_millis(50);
+        settings.max_bullets = 6;
+    }
+
+    hold_fire(&mut app);
+    advance(&mut app, 64);
+
+    assert_eq!(bullet_translations(&mut app).len(), 6);
+}
+
+#[test]
+fn bullets_expire_after_their_lifetime() {
+    let mut app = new_game();
+    app.world_mut().resource_mut::<BulletSettings>().lifetime = Duration::from_millis(500);
+
+    fire(&mut app);
+    advance(&mut app, 30);
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+
+    advance(&mut app, 2);
+    assert!(bullet_translations(&mut app).is_empty());
+}
+
+#[test]
+fn a_bullet_lifetime_can_be_overridden() {
+    let mut app = new_game();
+    let bullet = spawn_bullet(&mut app, Vec2::ZERO, Vec2::Y);
+    app.world_mut()
+        .entity_mut(bullet)
+        .insert(Lifetime::new(Duration::from_secs(5)));
+
+    advance(&mut app, 4 * 64);
+
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+}
+
+#[test]
+fn bullet_wrap_is_kept_in_the_settings_file() {
+    let path = scratch_file("bullets.ron");
+    let mut app = new_game_with(Asteroids::headless(ARENA).with_seed(0).with_settings(&path));
+
+    app.world_mut().resource_mut::<BulletSettings>().wrap = false;
+    app.update();
+    assert!(!Settings::load(&path).unwrap().bullets.wrap);
+
+    let app = new_game_with(Asteroids::headless(ARENA).with_seed(0).with_settings(&path));
+    assert!(!app.world().resource::<BulletSettings>().wrap);
+}
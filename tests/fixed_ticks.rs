@@ -0,0 +1,50 @@
+use asteroids::{AsteroidSize, ShipState};
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+mod common;
+
+use common::*;
+
+#[derive(Resource, Default)]
+struct ShipStates(Vec<ShipState>);
+
+fn trace_ship_state(state: Res<State<ShipState>>, mut trace: ResMut<ShipStates>) {
+    trace.0.push(state.get().clone());
+}
+
+// Plays the same game with several fixed ticks run in a single frame, like a slow machine would,
+// and traces the ship state every tick sees
+fn play(ticks_per_frame: u32) -> Vec<ShipState> {
+    let mut app = new_game();
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.init_resource::<ShipStates>()
+        .add_systems(FixedFirst, trace_ship_state)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(
+            timestep * ticks_per_frame,
+        ));
+
+    spawn_moving_asteroid(
+        &mut app,
+        Vec2::new(0., 100.),
+        Vec2::new(0., -100.),
+        AsteroidSize::Large,
+    );
+    advance(&mut app, 240);
+
+    app.world_mut().remove_resource::<ShipStates>().unwrap().0
+}
+
+#[test]
+fn state_changes_take_effect_on_the_next_tick_not_the_next_frame() {
+    let one_tick_per_frame = play(1);
+    assert!(one_tick_per_frame.contains(&ShipState::Destroyed));
+    assert_eq!(one_tick_per_frame.last(), Some(&ShipState::Flying));
+
+    for ticks_per_frame in [2, 3, 4] {
+        assert_eq!(
+            play(ticks_per_frame),
+            one_tick_per_frame,
+            "{ticks_per_frame} ticks per frame"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use asteroids::{ActivePowerUps, AsteroidSize, GameState, Player, PowerUp, Ship};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+fn game_state(app: &App) -> GameState {
+    app.world().resource::<State<GameState>>().get().clone()
+}
+
+fn lose_last_life(app: &mut App) {
+    app.world_mut()
+        .query::<&mut Player>()
+        .single_mut(app.world_mut())
+        .life_count = 1;
+    place_ship(app, Vec2::new(100., 100.));
+    spawn_asteroid(app, Vec2::new(100., 100.), AsteroidSize::Medium);
+
+    advance(app, 3);
+}
+
+#[test]
+fn losing_the_last_life_ends_the_game() {
+    let mut app = new_game();
+    lose_last_life(&mut app);
+
+    assert_eq!(game_state(&app), GameState::GameOver);
+    assert_eq!(player(&mut app).life_count, 0);
+}
+
+#[test]
+fn playfield_freezes_once_the_game_is_over() {
+    let mut app = new_game();
+    lose_last_life(&mut app);
+    let asteroid = spawn_moving_asteroid(
+        &mut app,
+        Vec2::new(-200., -200.),
+        Vec2::new(50., 0.),
+        AsteroidSize::Large,
+    );
+
+    advance(&mut app, 10);
+
+    let translation = app.world().get::<Transform>(asteroid).unwrap().translation;
+    assert_eq!(translation, Vec3::new(-200., -200., 0.));
+}
+
+#[test]
+fn playing_again_starts_a_fresh_game() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 0.), AsteroidSize::Small);
+    spawn_bullet(&mut app, Vec2::new(200., 0.), Vec2::Y);
+    lose_last_life(&mut app);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+
+    assert_eq!(game_state(&app), GameState::Playing);
+    let player = player(&mut app);
+    assert_eq!(player.life_count, 3);
+    assert_eq!(player.score, 0);
+    let ships = app
+        .world_mut()
+        .query_filtered::<(), With<Ship>>()
+        .iter(app.world())
+        .count();
+    assert_eq!(ships, 1);
+}
+
+#[test]
+fn accuracy_counts_the_shots_that_hit() {
+    let mut app = new_game();
+    assert_eq!(player(&mut app).accuracy(), None);
+
+    spawn_asteroid(&mut app, Vec2::new(0., 200.), AsteroidSize::Small);
+    fire(&mut app);
+    advance(&mut app, 30);
+    fire(&mut app);
+    advance(&mut app, 3);
+
+    let player = player(&mut app);
+    assert_eq!(player.shots_fired, 2);
+    assert_eq!(player.shots_hit, 1);
+    assert_eq!(player.accuracy(), Some(0.5));
+}
+
+#[test]
+fn point_blank_hits_are_counted_as_shots() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(0., 30.), AsteroidSize::Small);
+
+    fire(&mut app);
+    advance(&mut app, 3);
+
+    let player = player(&mut app);
+    assert_eq!(player.shots_hit, 1);
+    assert!(player.shots_hit <= player.shots_fired);
+    assert_eq!(player.accuracy(), Some(1.));
+}
+
+#[test]
+fn every_bullet_of_a_spread_shot_counts() {
+    let mut app = new_game();
+    app.world_mut()
+        .resource_mut::<ActivePowerUps>()
+        .activate(PowerUp::SpreadShot, Duration::from_secs(10));
+
+    fire(&mut app);
+    advance(&mut app, 1);
+
+    assert_eq!(player(&mut app).shots_fired, 3);
+}
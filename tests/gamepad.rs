@@ -0,0 +1,154 @@
+use asteroids::{ActiveGamepad, GamepadConfig, PauseState};
+use bevy::{
+    input::gamepad::{
+        GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadConnection,
+        GamepadConnectionEvent, GamepadEvent, GamepadInfo,
+    },
+    prelude::*,
+};
+
+mod common;
+
+use common::*;
+
+const PAD: Gamepad = Gamepad { id: 0 };
+
+fn connect(app: &mut App, gamepad: Gamepad) {
+    app.world_mut()
+        .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "Test pad".to_string(),
+            }),
+        )));
+    app.update();
+}
+
+fn disconnect(app: &mut App, gamepad: Gamepad) {
+    app.world_mut()
+        .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Disconnected,
+        )));
+    app.update();
+}
+
+fn move_stick(app: &mut App, x: f32) {
+    app.world_mut()
+        .send_event(GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+            PAD,
+            GamepadAxisType::LeftStickX,
+            x,
+        )));
+    app.update();
+}
+
+fn push_button(app: &mut App, button_type: GamepadButtonType, value: f32) {
+    app.world_mut()
+        .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+            PAD,
+            button_type,
+            value,
+        )));
+    app.update();
+}
+
+fn rotation(app: &mut App) -> f32 {
+    ship_transform(app).rotation.to_euler(EulerRot::XYZ).2
+}
+
+// How far the ship turns in a few ticks with the stick pushed that far right
+fn turn_with_stick(x: f32) -> f32 {
+    let mut app = new_game();
+    connect(&mut app, PAD);
+    move_stick(&mut app, x);
+    let start = rotation(&mut app);
+    advance(&mut app, 5);
+    start - rotation(&mut app)
+}
+
+#[test]
+fn stick_deflection_scales_the_rotation() {
+    let full = turn_with_stick(1.);
+    let half = turn_with_stick(0.575);
+
+    assert!(full > 0.);
+    assert!((half / full - 0.5).abs() < 0.05, "{half} / {full}");
+}
+
+#[test]
+fn stick_inside_the_dead_zone_does_nothing() {
+    assert_eq!(turn_with_stick(0.1), 0.);
+}
+
+#[test]
+fn dead_zone_is_configurable() {
+    let mut app = new_game();
+    app.insert_resource(GamepadConfig { dead_zone: 0.5 });
+    connect(&mut app, PAD);
+    move_stick(&mut app, 0.4);
+    let start = rotation(&mut app);
+
+    advance(&mut app, 5);
+
+    assert_eq!(rotation(&mut app), start);
+}
+
+#[test]
+fn trigger_thrusts_and_face_button_fires() {
+    let mut app = new_game();
+    connect(&mut app, PAD);
+
+    push_button(&mut app, GamepadButtonType::RightTrigger2, 1.);
+    advance(&mut app, 30);
+    assert!(ship_transform(&mut app).translation.y > 0.);
+
+    push_button(&mut app, GamepadButtonType::South, 1.);
+    push_button(&mut app, GamepadButtonType::South, 0.);
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+}
+
+#[test]
+fn second_gamepad_takes_over_when_the_first_is_unplugged() {
+    let mut app = new_game();
+    let other = Gamepad::new(1);
+    connect(&mut app, PAD);
+    connect(&mut app, other);
+    assert_eq!(app.world().resource::<ActiveGamepad>().0, Some(PAD));
+
+    disconnect(&mut app, PAD);
+    assert_eq!(app.world().resource::<ActiveGamepad>().0, Some(other));
+
+    disconnect(&mut app, other);
+    assert_eq!(app.world().resource::<ActiveGamepad>().0, None);
+}
+
+#[test]
+fn unplugging_a_gamepad_mid_game_pauses() {
+    let mut app = new_game();
+    connect(&mut app, PAD);
+
+    disconnect(&mut app, PAD);
+    app.update();
+
+    assert_eq!(
+        app.world().resource::<State<PauseState>>().get(),
+        &PauseState::Paused
+    );
+}
+
+#[test]
+fn unplugging_a_spare_gamepad_does_not_pause() {
+    let mut app = new_game();
+    let other = Gamepad::new(1);
+    connect(&mut app, PAD);
+    connect(&mut app, other);
+
+    disconnect(&mut app, other);
+    app.update();
+
+    assert_eq!(
+        app.world().resource::<State<PauseState>>().get(),
+        &PauseState::Running
+    );
+}
@@ -0,0 +1,146 @@
+use asteroids::{AsteroidSize, Asteroids, GameState, HighScore, HighScores, Player};
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput, NativeKeyCode},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+mod common;
+
+use common::*;
+
+fn entry(initials: &str, score: i32) -> HighScore {
+    HighScore {
+        initials: initials.to_string(),
+        score,
+        wave: 1,
+        date: "2024-01-01".to_string(),
+    }
+}
+
+// Ramming the asteroid that ends the game is worth another 50 points
+fn end_game_with_score(app: &mut App, score: i32) {
+    {
+        let mut player = app
+            .world_mut()
+            .query::<&mut Player>()
+            .single_mut(app.world_mut());
+        player.life_count = 1;
+        player.score = score;
+    }
+    place_ship(app, Vec2::new(100., 100.));
+    spawn_asteroid(app, Vec2::new(100., 100.), AsteroidSize::Medium);
+
+    advance(app, 3);
+    assert_eq!(
+        app.world().resource::<State<GameState>>().get(),
+        &GameState::GameOver
+    );
+}
+
+fn press(app: &mut App, key: Key) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world_mut().send_event(KeyboardInput {
+            key_code: KeyCode::Unidentified(NativeKeyCode::Unidentified),
+            logical_key: key.clone(),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+    app.update();
+}
+
+fn type_initials(app: &mut App, initials: &str) {
+    for letter in initials.chars() {
+        press(app, Key::Character(letter.to_string().into()));
+    }
+    press(app, Key::Enter);
+}
+
+#[test]
+fn table_keeps_the_best_scores_in_order() {
+    let mut high_scores = HighScores::default();
+    for score in 1..=12 {
+        high_scores.insert(entry("AAA", score * 100));
+    }
+    high_scores.insert(entry("BBB", 550));
+
+    let scores: Vec<i32> = high_scores
+        .entries
+        .iter()
+        .map(|entry| entry.score)
+        .collect();
+    assert_eq!(
+        scores,
+        [1200, 1100, 1000, 900, 800, 700, 600, 550, 500, 400]
+    );
+    assert!(!high_scores.qualifies(400));
+    assert!(high_scores.qualifies(401));
+}
+
+#[test]
+fn nothing_qualifies_without_points() {
+    assert!(!HighScores::default().qualifies(0));
+    assert!(HighScores::default().qualifies(10));
+}
+
+#[test]
+fn table_survives_a_save_and_load() {
+    let path = scratch_file("saved.ron");
+    let mut high_scores = HighScores::default();
+    high_scores.insert(entry("ABC", 1000));
+    high_scores.insert(entry("XYZ", 20));
+
+    high_scores.save(&path).unwrap();
+
+    assert_eq!(HighScores::load(&path).unwrap(), high_scores);
+}
+
+#[test]
+fn qualifying_score_is_recorded_under_the_typed_initials() {
+    let path = scratch_file("typed.ron");
+    let mut app = new_game_with(
+        Asteroids::headless(ARENA)
+            .with_seed(0)
+            .with_high_scores(&path),
+    );
+    end_game_with_score(&mut app, 1180);
+
+    type_initials(&mut app, "jd");
+    press(&mut app, Key::Backspace);
+    type_initials(&mut app, "ax");
+
+    let expected = HighScore {
+        date: String::new(),
+        ..entry("JAX", 1230)
+    };
+    let recorded = &app.world().resource::<HighScores>().entries;
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(
+        HighScore {
+            date: String::new(),
+            ..recorded[0].clone()
+        },
+        expected
+    );
+    assert_eq!(HighScores::load(&path).unwrap().entries, *recorded);
+}
+
+#[test]
+fn leaving_before_confirming_still_records_the_score() {
+    let mut app = new_game();
+    end_game_with_score(&mut app, 450);
+    press(&mut app, Key::Character("q".into()));
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+
+    let recorded = &app.world().resource::<HighScores>().entries;
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].initials, "Q--");
+    assert_eq!(recorded[0].score, 500);
+}
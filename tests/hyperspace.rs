@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use asteroids::{AsteroidSize, HyperspaceSettings, ShipState};
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+mod common;
+
+use common::*;
+
+fn new_game_with_explode_chance(explode_chance: f64) -> App {
+    let mut app = new_game();
+    app.insert_resource(HyperspaceSettings {
+        vanish_time: Duration::from_millis(500),
+        explode_chance,
+        cooldown: Duration::from_secs(1),
+    });
+    app
+}
+
+fn hold_down(app: &mut App, held: bool) {
+    app.world_mut().send_event(KeyboardInput {
+        key_code: KeyCode::KeyS,
+        logical_key: Key::Character("s".into()),
+        state: if held {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Released
+        },
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+#[test]
+fn ship_vanishes_and_reappears_elsewhere() {
+    let mut app = new_game_with_explode_chance(0.);
+    hold_down(&mut app, true);
+    advance(&mut app, 2);
+    hold_down(&mut app, false);
+
+    assert_eq!(ship_state(&app), ShipState::Hyperspace);
+    assert_eq!(ship_transform(&mut app).translation, Vec3::ZERO);
+
+    advance(&mut app, 32);
+
+    assert_eq!(ship_state(&app), ShipState::Flying);
+    assert_ne!(ship_transform(&mut app).translation, Vec3::ZERO);
+    assert_eq!(player(&mut app).life_count, 3);
+}
+
+#[test]
+fn ship_can_explode_on_reentry() {
+    let mut app = new_game_with_explode_chance(1.);
+    hold_down(&mut app, true);
+    advance(&mut app, 2);
+    hold_down(&mut app, false);
+
+    advance(&mut app, 34);
+
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+    assert_eq!(player(&mut app).life_count, 2);
+}
+
+#[test]
+fn jumps_need_to_cool_down() {
+    let mut app = new_game_with_explode_chance(0.);
+    hold_down(&mut app, true);
+    advance(&mut app, 34);
+    hold_down(&mut app, false);
+    assert_eq!(ship_state(&app), ShipState::Flying);
+
+    // pressed again right away, the ship stays put
+    hold_down(&mut app, true);
+    advance(&mut app, 2);
+    hold_down(&mut app, false);
+    assert_eq!(ship_state(&app), ShipState::Flying);
+
+    advance(&mut app, 64);
+    hold_down(&mut app, true);
+    advance(&mut app, 2);
+    assert_eq!(ship_state(&app), ShipState::Hyperspace);
+}
+
+#[test]
+fn holding_the_key_jumps_only_once() {
+    let mut app = new_game_with_explode_chance(0.);
+    hold_down(&mut app, true);
+    advance(&mut app, 34 + 64 + 8);
+
+    assert_eq!(ship_state(&app), ShipState::Flying);
+}
+
+#[test]
+fn ship_cannot_fire_from_hyperspace() {
+    let mut app = new_game_with_explode_chance(0.);
+    hold_down(&mut app, true);
+    advance(&mut app, 2);
+    hold_down(&mut app, false);
+    assert_eq!(ship_state(&app), ShipState::Hyperspace);
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+    advance(&mut app, 8);
+
+    assert!(bullet_translations(&mut app).is_empty());
+}
+
+#[test]
+fn pressing_hyperspace_while_destroyed_does_not_jump_the_next_ship() {
+    let mut app = new_game_with_explode_chance(1.);
+    spawn_asteroid(&mut app, Vec2::ZERO, AsteroidSize::Small);
+    advance(&mut app, 2);
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+
+    hold_down(&mut app, true);
+    advance(&mut app, 2);
+    hold_down(&mut app, false);
+
+    advance(&mut app, 3 * 64 + 4);
+    assert_eq!(ship_state(&app), ShipState::Flying);
+    assert_eq!(player(&mut app).life_count, 2);
+}
@@ -0,0 +1,155 @@
+use asteroids::{Asteroids, GameControl, KeyBindings, PauseState, Settings};
+use bevy::{input::keyboard::Key, prelude::*};
+
+mod common;
+
+use common::*;
+
+#[test]
+fn binding_a_key_takes_it_from_the_other_controls() {
+    let mut bindings = KeyBindings::default();
+
+    bindings.bind(GameControl::Fire, KeyCode::KeyW);
+
+    assert_eq!(bindings.keys(GameControl::Fire), [KeyCode::KeyW]);
+    assert_eq!(bindings.keys(GameControl::Thrust), [KeyCode::ArrowUp]);
+}
+
+#[test]
+fn taking_the_last_key_of_a_control_swaps_the_keys() {
+    let mut bindings = KeyBindings::default();
+
+    bindings.bind(GameControl::Fire, KeyCode::Escape);
+    bindings.bind(GameControl::Shield, KeyCode::KeyP);
+
+    assert_eq!(bindings.keys(GameControl::Shield), [KeyCode::KeyP]);
+    assert_eq!(
+        bindings.keys(GameControl::Pause),
+        [KeyCode::KeyE, KeyCode::ShiftLeft]
+    );
+    assert!(GameControl::ALL
+        .iter()
+        .all(|&control| !bindings.keys(control).is_empty()));
+}
+
+#[test]
+fn rebound_thrust_moves_the_ship() {
+    let mut app = new_game();
+    app.world_mut()
+        .resource_mut::<KeyBindings>()
+        .bind(GameControl::Thrust, KeyCode::KeyI);
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyI);
+    advance(&mut app, 30);
+
+    assert!(ship_transform(&mut app).translation.y > 0.);
+}
+
+#[test]
+fn rebound_pause_key_pauses() {
+    let mut app = new_game();
+    app.world_mut()
+        .resource_mut::<KeyBindings>()
+        .bind(GameControl::Pause, KeyCode::KeyQ);
+
+    tap(&mut app, KeyCode::KeyP, Key::Character("p".into()));
+    assert_eq!(
+        app.world().resource::<State<PauseState>>().get(),
+        &PauseState::Running
+    );
+
+    tap(&mut app, KeyCode::KeyQ, Key::Character("q".into()));
+    assert_eq!(
+        app.world().resource::<State<PauseState>>().get(),
+        &PauseState::Paused
+    );
+}
+
+#[test]
+fn bindings_come_from_the_settings_file() {
+    let path = scratch_file("bindings.ron");
+    let mut settings = Settings::default();
+    settings.key_bindings.bind(GameControl::Fire, KeyCode::KeyF);
+    settings.save(&path).unwrap();
+
+    let mut app = new_game_with(Asteroids::headless(ARENA).with_seed(0).with_settings(&path));
+    fire(&mut app);
+    assert!(bullet_translations(&mut app).is_empty());
+
+    tap(&mut app, KeyCode::KeyF, Key::Character("f".into()));
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+}
+
+#[test]
+fn changed_bindings_are_saved() {
+    let path = scratch_file("saved_bindings.ron");
+    let mut app = new_game_with(Asteroids::headless(ARENA).with_seed(0).with_settings(&path));
+
+    app.world_mut()
+        .resource_mut::<KeyBindings>()
+        .bind(GameControl::Hyperspace, KeyCode::KeyH);
+    app.update();
+
+    let saved = Settings::load(&path).unwrap();
+    assert_eq!(
+        saved.key_bindings.keys(GameControl::Hyperspace),
+        [KeyCode::KeyH]
+    );
+}
+
+#[test]
+fn missing_controls_fall_back_to_the_default_keys() {
+    let path = scratch_file("partial.ron");
+    std::fs::write(&path, "(key_bindings: (fire: [KeyF]))").unwrap();
+
+    let settings = Settings::load(&path).unwrap();
+
+    assert_eq!(
+        settings.key_bindings.keys(GameControl::Fire),
+        [KeyCode::KeyF]
+    );
+    assert_eq!(
+        settings.key_bindings.keys(GameControl::Thrust),
+        KeyBindings::default().keys(GameControl::Thrust)
+    );
+}
+
+#[test]
+fn rebinding_control_after_control_leaves_none_without_a_key() {
+    let mut bindings = KeyBindings::default();
+
+    for (control, key) in [
+        (GameControl::Fire, KeyCode::Escape),
+        (GameControl::Shield, KeyCode::KeyP),
+        (GameControl::Thrust, KeyCode::KeyE),
+        (GameControl::Pause, KeyCode::KeyW),
+        (GameControl::Hyperspace, KeyCode::ArrowUp),
+        (GameControl::RotateLeft, KeyCode::ShiftLeft),
+    ] {
+        assert!(bindings.bind(control, key), "{control:?} to {key:?}");
+        assert_eq!(bindings.keys(control), [key]);
+        assert!(
+            GameControl::ALL
+                .iter()
+                .all(|&control| !bindings.keys(control).is_empty()),
+            "{bindings:?}"
+        );
+    }
+}
+
+#[test]
+fn a_rebind_that_would_leave_a_control_without_a_key_is_refused() {
+    // only a hand-edited settings file gets two controls the very same single key
+    let mut bindings = KeyBindings {
+        shield: vec![KeyCode::KeyE],
+        hyperspace: vec![KeyCode::KeyE],
+        ..default()
+    };
+
+    assert!(!bindings.bind(GameControl::Fire, KeyCode::KeyE));
+    assert_eq!(bindings.keys(GameControl::Fire), [KeyCode::Space]);
+    assert_eq!(bindings.keys(GameControl::Shield), [KeyCode::KeyE]);
+    assert_eq!(bindings.keys(GameControl::Hyperspace), [KeyCode::KeyE]);
+}
@@ -0,0 +1,70 @@
+use asteroids::AsteroidSize;
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+// Runs one second of game time at the given fixed update rate
+fn one_second_at(hz: u32, mut setup: impl FnMut(&mut App)) -> App {
+    let mut app = new_game();
+    set_fixed_rate(&mut app, hz as f64);
+    setup(&mut app);
+    advance(&mut app, hz);
+    app
+}
+
+#[test]
+fn asteroids_move_in_units_per_second() {
+    for hz in [32, 64, 128] {
+        let mut asteroid = Entity::PLACEHOLDER;
+        let app = one_second_at(hz, |app| {
+            asteroid = spawn_moving_asteroid(
+                app,
+                Vec2::new(-100., 150.),
+                Vec2::new(150., 50.),
+                AsteroidSize::Medium,
+            );
+        });
+
+        let translation = app.world().get::<Transform>(asteroid).unwrap().translation;
+        assert!(
+            translation.abs_diff_eq(Vec3::new(50., 200., 0.), 0.01),
+            "{hz} Hz: {translation}"
+        );
+    }
+}
+
+#[test]
+fn bullets_cover_the_same_distance_at_any_rate() {
+    let distances: Vec<f32> = [32, 64, 128]
+        .map(|hz| {
+            let mut app = one_second_at(hz, |_| {});
+            fire(&mut app);
+            let start = bullet_translations(&mut app)[0];
+            advance(&mut app, hz / 2);
+            bullet_translations(&mut app)[0].distance(start)
+        })
+        .to_vec();
+
+    assert!((distances[0] - distances[1]).abs() < 0.01, "{distances:?}");
+    assert!((distances[1] - distances[2]).abs() < 0.01, "{distances:?}");
+}
+
+#[test]
+fn ship_thrust_barely_depends_on_the_rate() {
+    let heights = [32, 128].map(|hz| {
+        let mut app = one_second_at(hz, |app| {
+            app.world_mut()
+                .resource_mut::<ButtonInput<KeyCode>>()
+                .press(KeyCode::KeyW);
+        });
+        ship_transform(&mut app).translation.y
+    });
+
+    // a second of thrust covers about half the acceleration, give or take a tick
+    assert!(
+        heights.iter().all(|height| (140. ..165.).contains(height)),
+        "{heights:?}"
+    );
+}
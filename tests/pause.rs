@@ -0,0 +1,107 @@
+use asteroids::{AsteroidSize, GameState, PauseState, ShipState};
+use bevy::{input::keyboard::Key, prelude::*};
+
+mod common;
+
+use common::*;
+
+fn pause_state(app: &App) -> Option<PauseState> {
+    app.world()
+        .get_resource::<State<PauseState>>()
+        .map(|state| state.get().clone())
+}
+
+fn translation(app: &App, entity: Entity) -> Vec3 {
+    app.world().get::<Transform>(entity).unwrap().translation
+}
+
+#[test]
+fn p_and_escape_toggle_the_pause() {
+    let mut app = new_game();
+
+    tap(&mut app, KeyCode::KeyP, Key::Character("p".into()));
+    assert_eq!(pause_state(&app), Some(PauseState::Paused));
+
+    tap(&mut app, KeyCode::Escape, Key::Escape);
+    assert_eq!(pause_state(&app), Some(PauseState::Running));
+}
+
+#[test]
+fn pausing_freezes_the_playfield() {
+    let mut app = new_game();
+    let asteroid = spawn_moving_asteroid(
+        &mut app,
+        Vec2::new(-200., -200.),
+        Vec2::new(50., 0.),
+        AsteroidSize::Large,
+    );
+    tap(&mut app, KeyCode::Escape, Key::Escape);
+    let paused_at = translation(&app, asteroid);
+
+    for _ in 0..10 {
+        app.update();
+    }
+    fire(&mut app);
+
+    assert_eq!(translation(&app, asteroid), paused_at);
+    assert!(bullet_translations(&mut app).is_empty());
+
+    tap(&mut app, KeyCode::Escape, Key::Escape);
+    advance(&mut app, 2);
+
+    assert!(translation(&app, asteroid).x > paused_at.x);
+}
+
+#[test]
+fn quitting_to_the_menu_while_paused_lets_time_run_again() {
+    let mut app = new_game();
+    tap(&mut app, KeyCode::KeyP, Key::Character("p".into()));
+    assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+
+    assert_eq!(pause_state(&app), None);
+    assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+}
+
+#[test]
+fn quitting_while_the_ship_is_destroyed_leaves_nothing_behind() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::ZERO, AsteroidSize::Small);
+    advance(&mut app, 2);
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    clear_asteroids(&mut app);
+
+    spawn_asteroid(&mut app, Vec2::ZERO, AsteroidSize::Small);
+    advance(&mut app, 2);
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+
+    // three seconds to respawn, with only the one wreck to clear away
+    advance(&mut app, 3 * 64 + 2);
+    assert_eq!(ship_state(&app), ShipState::Flying);
+}
+
+#[test]
+fn quitting_to_the_menu_clears_the_game_asteroids() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 200.), AsteroidSize::Small);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Small), 0);
+}
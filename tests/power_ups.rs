@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use asteroids::{ActivePowerUps, AsteroidSize, PickupBundle, Position, PowerUp, Shield, Velocity};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+fn spawn_pickup(app: &mut App, position: Vec2, power_up: PowerUp) {
+    app.world_mut().spawn(PickupBundle::new(
+        Position(position),
+        Velocity(Vec3::ZERO),
+        power_up,
+    ));
+}
+
+fn activate(app: &mut App, power_up: PowerUp) {
+    app.world_mut()
+        .resource_mut::<ActivePowerUps>()
+        .activate(power_up, Duration::from_secs(10));
+}
+
+fn is_active(app: &App, power_up: PowerUp) -> bool {
+    app.world().resource::<ActivePowerUps>().is_active(power_up)
+}
+
+#[test]
+fn ship_collects_pickups_on_contact() {
+    let mut app = new_game();
+    spawn_pickup(&mut app, Vec2::new(5., 0.), PowerUp::SpreadShot);
+    spawn_pickup(&mut app, Vec2::new(200., 0.), PowerUp::Piercing);
+
+    advance(&mut app, 2);
+
+    assert!(is_active(&app, PowerUp::SpreadShot));
+    assert!(!is_active(&app, PowerUp::Piercing));
+}
+
+#[test]
+fn extra_life_is_granted_right_away() {
+    let mut app = new_game();
+    spawn_pickup(&mut app, Vec2::ZERO, PowerUp::ExtraLife);
+
+    advance(&mut app, 2);
+
+    assert_eq!(player(&mut app).life_count, 4);
+    assert!(!is_active(&app, PowerUp::ExtraLife));
+}
+
+#[test]
+fn power_ups_run_out() {
+    let mut app = new_game();
+    activate(&mut app, PowerUp::RapidFire);
+
+    advance(&mut app, 64 * 9);
+    assert!(is_active(&app, PowerUp::RapidFire));
+
+    advance(&mut app, 64 + 1);
+    assert!(!is_active(&app, PowerUp::RapidFire));
+}
+
+#[test]
+fn spread_shot_fires_three_bullets() {
+    let mut app = new_game();
+    activate(&mut app, PowerUp::SpreadShot);
+
+    fire(&mut app);
+    advance(&mut app, 1);
+
+    assert_eq!(bullet_translations(&mut app).len(), 3);
+}
+
+#[test]
+fn rapid_fire_keeps_firing_while_held() {
+    let mut app = new_game();
+    activate(&mut app, PowerUp::RapidFire);
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+    advance(&mut app, 32);
+
+    // half a second at one shot every tenth of a second
+    assert_eq!(bullet_translations(&mut app).len(), 5);
+}
+
+#[test]
+fn shield_pickup_recharges_the_shield() {
+    let mut app = new_game();
+    let ship = place_ship(&mut app, Vec2::ZERO);
+    app.world_mut().get_mut::<Shield>(ship).unwrap().energy = 0.;
+    spawn_pickup(&mut app, Vec2::ZERO, PowerUp::Shield);
+
+    advance(&mut app, 2);
+
+    assert_eq!(app.world().get::<Shield>(ship).unwrap().charge(), 1.);
+    assert!(!is_active(&app, PowerUp::Shield));
+}
+
+#[test]
+fn piercing_bullets_fly_through_asteroids() {
+    let mut app = new_game();
+    activate(&mut app, PowerUp::Piercing);
+    spawn_asteroid(&mut app, Vec2::new(0., 100.), AsteroidSize::Small);
+    spawn_asteroid(&mut app, Vec2::new(0., 200.), AsteroidSize::Small);
+
+    fire(&mut app);
+    advance(&mut app, 32);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Small), 0);
+    assert_eq!(bullet_translations(&mut app).len(), 1);
+    assert_eq!(player(&mut app).score, 200);
+}
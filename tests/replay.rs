@@ -0,0 +1,127 @@
+use asteroids::{
+    Arena, Asteroid, AsteroidSize, Asteroids, BulletSettings, DifficultyCurve, GameState, Ramp,
+    Replay, Ship,
+};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+fn asteroid_positions(app: &mut App) -> Vec<Vec3> {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Asteroid>>()
+        .iter(app.world())
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+fn ship_translation(app: &mut App) -> Vec3 {
+    app.world_mut()
+        .query_filtered::<&Transform, With<Ship>>()
+        .single(app.world())
+        .translation
+}
+
+fn end_game(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+}
+
+#[test]
+fn same_seed_spawns_same_asteroids() {
+    let spawn = |seed| {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(Asteroids::headless(Arena::new(800., 600.)).with_seed(seed));
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+        asteroid_positions(&mut app)
+    };
+
+    assert_eq!(spawn(7), spawn(7));
+    assert_ne!(spawn(7), spawn(8));
+}
+
+#[test]
+fn replay_reproduces_recorded_game() {
+    let path = scratch_file("replay.ron");
+
+    let mut recorded = new_game_with(Asteroids::headless(ARENA).recording(&path));
+    spawn_asteroid(&mut recorded, Vec2::new(150., -150.), AsteroidSize::Large);
+    recorded
+        .world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyW);
+    advance(&mut recorded, 60);
+    let recorded_ship = ship_translation(&mut recorded);
+    assert_ne!(recorded_ship, Vec3::ZERO);
+    end_game(&mut recorded);
+
+    let replay = Replay::load(&path).unwrap();
+    assert!(replay.ticks.iter().any(|tick| tick.movement.is_some()));
+
+    let mut replayed = new_game_with(Asteroids::headless(ARENA).replaying(replay));
+    spawn_asteroid(&mut replayed, Vec2::new(150., -150.), AsteroidSize::Large);
+    advance(&mut replayed, 60);
+
+    assert_eq!(ship_translation(&mut replayed), recorded_ship);
+}
+
+#[test]
+fn closing_the_app_mid_game_saves_the_replay() {
+    let path = scratch_file("replay.ron");
+
+    let mut app = new_game_with(Asteroids::headless(ARENA).recording(&path));
+    advance(&mut app, 30);
+    app.world_mut().send_event(AppExit::Success);
+    app.update();
+
+    let replay = Replay::load(&path).unwrap();
+    assert!(replay.ticks.len() >= 30);
+}
+
+#[test]
+fn replay_brings_its_own_arena_difficulty_and_bullet_settings() {
+    let path = scratch_file("replay.ron");
+    let difficulty = DifficultyCurve {
+        asteroids: Ramp {
+            first: 2.,
+            per_wave: 1.,
+            max: 6.,
+        },
+        ..default()
+    };
+
+    let mut recorded = new_game_with(
+        Asteroids::headless(ARENA)
+            .with_difficulty(difficulty.clone())
+            .recording(&path),
+    );
+    advance(&mut recorded, 10);
+    recorded.world_mut().resource_mut::<BulletSettings>().wrap = false;
+    recorded
+        .world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+    advance(&mut recorded, 50);
+    let recorded_bullets = bullet_translations(&mut recorded);
+    assert!(!recorded_bullets.is_empty());
+    end_game(&mut recorded);
+
+    let replay = Replay::load(&path).unwrap();
+    let mut replayed = new_game_with(Asteroids::headless(Arena::new(400., 300.)).replaying(replay));
+    assert_eq!(*replayed.world().resource::<Arena>(), ARENA);
+    assert_eq!(*replayed.world().resource::<DifficultyCurve>(), difficulty);
+    assert!(replayed.world().resource::<BulletSettings>().wrap);
+
+    advance(&mut replayed, 60);
+
+    assert!(!replayed.world().resource::<BulletSettings>().wrap);
+    assert_eq!(bullet_translations(&mut replayed), recorded_bullets);
+}
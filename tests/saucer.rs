@@ -0,0 +1,85 @@
+use std::f32::consts::PI;
+
+use asteroids::{
+    AsteroidSize, BulletBundle, EnemyBullet, Heading, Position, SaucerSize, ShipState, Velocity,
+};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn saucer_shows_up_after_a_while() {
+    let mut app = new_game();
+
+    advance(&mut app, 64 * 9);
+    assert_eq!(saucer_count(&mut app), 0);
+
+    // the asteroids that came back in the meantime must not get in the way
+    clear_asteroids(&mut app);
+
+    advance(&mut app, 64 + 1);
+    assert_eq!(saucer_count(&mut app), 1);
+}
+
+#[test]
+fn bullet_destroys_saucer_for_bonus_score() {
+    let mut app = new_game();
+    spawn_saucer(&mut app, Vec2::new(200., 0.), 1., SaucerSize::Small);
+    spawn_bullet(&mut app, Vec2::new(200., 0.), Vec2::Y);
+
+    advance(&mut app, 3);
+
+    assert_eq!(saucer_count(&mut app), 0);
+    assert_eq!(player(&mut app).score, 1000);
+}
+
+#[test]
+fn saucer_smashes_asteroids_without_scoring() {
+    let mut app = new_game();
+    spawn_saucer(&mut app, Vec2::new(-200., 100.), 1., SaucerSize::Large);
+    spawn_asteroid(&mut app, Vec2::new(-200., 100.), AsteroidSize::Small);
+
+    advance(&mut app, 3);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Small), 0);
+    assert_eq!(saucer_count(&mut app), 0);
+    assert_eq!(player(&mut app).score, 0);
+}
+
+#[test]
+fn saucer_fires_roughly_at_the_ship() {
+    let mut app = new_game();
+    spawn_saucer(&mut app, Vec2::new(200., 150.), -1., SaucerSize::Small);
+
+    let mut fired = Vec::new();
+    while fired.is_empty() {
+        advance(&mut app, 1);
+        fired = app
+            .world_mut()
+            .query_filtered::<(&Transform, &Velocity), With<EnemyBullet>>()
+            .iter(app.world())
+            .map(|(transform, velocity)| (transform.translation, velocity.0))
+            .collect();
+    }
+
+    let (position, velocity) = fired[0];
+    let to_ship = -position.truncate();
+    let miss = velocity.truncate().angle_between(to_ship).abs();
+    assert!(miss <= PI / 24. + 0.01, "missed by {miss} radians");
+}
+
+#[test]
+fn enemy_bullet_destroys_ship() {
+    let mut app = new_game();
+    app.world_mut().spawn((
+        BulletBundle::new(Heading(Vec3::X), Position(Vec2::new(-10., 0.))),
+        EnemyBullet,
+    ));
+
+    advance(&mut app, 3);
+
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+    assert_eq!(player(&mut app).life_count, 2);
+}
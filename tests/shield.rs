@@ -0,0 +1,124 @@
+use asteroids::{
+    AsteroidSize, BulletBundle, EnemyBullet, Heading, Position, SaucerSize, Shield, Ship,
+    ShipState, Velocity,
+};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+fn hold_shield(app: &mut App, held: bool) {
+    let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+    if held {
+        keyboard.press(KeyCode::KeyE);
+    } else {
+        keyboard.release(KeyCode::KeyE);
+    }
+}
+
+fn shield(app: &mut App) -> Shield {
+    *app.world_mut()
+        .query_filtered::<&Shield, With<Ship>>()
+        .single(app.world())
+}
+
+#[test]
+fn shield_bounces_asteroids() {
+    let mut app = new_game();
+    hold_shield(&mut app, true);
+    let asteroid = spawn_moving_asteroid(
+        &mut app,
+        Vec2::new(60., 0.),
+        Vec2::new(-128., 0.),
+        AsteroidSize::Medium,
+    );
+
+    advance(&mut app, 32);
+
+    assert_eq!(ship_state(&app), ShipState::Flying);
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Medium), 1);
+    assert!(app.world().get::<Velocity>(asteroid).unwrap().0.x > 0.);
+    assert!(shield(&mut app).energy < 0.75);
+}
+
+#[test]
+fn shield_absorbs_enemy_bullets() {
+    let mut app = new_game();
+    hold_shield(&mut app, true);
+    advance(&mut app, 1);
+    app.world_mut().spawn((
+        BulletBundle::new(Heading(Vec3::X), Position(Vec2::new(-10., 0.))),
+        EnemyBullet,
+    ));
+
+    advance(&mut app, 3);
+
+    assert_eq!(ship_state(&app), ShipState::Flying);
+    assert_eq!(player(&mut app).life_count, 3);
+}
+
+#[test]
+fn shield_runs_out_and_recharges() {
+    let mut app = new_game();
+    hold_shield(&mut app, true);
+
+    // five seconds drain it completely
+    advance(&mut app, 64 * 5 + 1);
+    assert_eq!(shield(&mut app).energy, 0.);
+    assert!(!shield(&mut app).is_up());
+
+    spawn_asteroid(&mut app, Vec2::ZERO, AsteroidSize::Small);
+    advance(&mut app, 2);
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+}
+
+#[test]
+fn shield_recharges_while_down() {
+    let mut app = new_game();
+    hold_shield(&mut app, true);
+    advance(&mut app, 64);
+    let drained = shield(&mut app).energy;
+
+    hold_shield(&mut app, false);
+    advance(&mut app, 64);
+
+    assert!(shield(&mut app).energy > drained);
+}
+
+#[test]
+fn shield_stops_a_ramming_saucer() {
+    let mut app = new_game();
+    hold_shield(&mut app, true);
+    advance(&mut app, 1);
+    spawn_saucer(&mut app, Vec2::ZERO, 1., SaucerSize::Large);
+
+    advance(&mut app, 3);
+
+    assert_eq!(saucer_count(&mut app), 0);
+    assert_eq!(ship_state(&app), ShipState::Flying);
+    assert_eq!(player(&mut app).life_count, 3);
+    assert!(shield(&mut app).energy < 0.75);
+}
+
+#[test]
+fn shield_gives_out_partway_through_a_tick() {
+    let mut app = new_game();
+    hold_shield(&mut app, true);
+    advance(&mut app, 1);
+    let ship = place_ship(&mut app, Vec2::ZERO);
+    app.world_mut().get_mut::<Shield>(ship).unwrap().energy = 0.2;
+
+    // both arrive in the same tick, there is only energy left for one of them
+    for side in [-1., 1.] {
+        spawn_moving_asteroid(
+            &mut app,
+            Vec2::new(side * 28., 0.),
+            Vec2::new(side * -640., 0.),
+            AsteroidSize::Small,
+        );
+    }
+    advance(&mut app, 3);
+
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+}
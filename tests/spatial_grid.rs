@@ -0,0 +1,62 @@
+use asteroids::{Arena, SpatialGrid};
+use bevy::prelude::*;
+
+fn grid() -> SpatialGrid {
+    SpatialGrid::new(&Arena::new(800., 600.), 80.)
+}
+
+fn candidates(grid: &SpatialGrid, position: Vec2, radius: f32) -> Vec<Entity> {
+    grid.candidates(position, radius).collect()
+}
+
+#[test]
+fn finds_nearby_and_skips_distant_entities() {
+    let mut grid = grid();
+    let near = Entity::from_raw(1);
+    let far = Entity::from_raw(2);
+    grid.insert(near, Vec2::new(10., 10.), 10.);
+    grid.insert(far, Vec2::new(300., -200.), 10.);
+
+    assert_eq!(candidates(&grid, Vec2::ZERO, 5.), vec![near]);
+}
+
+#[test]
+fn finds_entities_across_the_arena_edges() {
+    let mut grid = grid();
+    let left = Entity::from_raw(1);
+    let bottom = Entity::from_raw(2);
+    grid.insert(left, Vec2::new(-395., 0.), 10.);
+    grid.insert(bottom, Vec2::new(0., -295.), 10.);
+
+    assert_eq!(candidates(&grid, Vec2::new(398., 0.), 2.), vec![left]);
+    assert_eq!(candidates(&grid, Vec2::new(0., 298.), 2.), vec![bottom]);
+}
+
+#[test]
+fn returns_every_entity_once_for_huge_queries() {
+    let mut grid = grid();
+    for index in 0..20 {
+        grid.insert(
+            Entity::from_raw(index),
+            Vec2::new(index as f32 * 40. - 400., index as f32 * 30. - 300.),
+            5.,
+        );
+    }
+
+    let mut found = candidates(&grid, Vec2::ZERO, 2000.);
+    found.sort();
+    found.dedup();
+
+    assert_eq!(found.len(), 20);
+    assert_eq!(grid.candidates(Vec2::ZERO, 2000.).count(), 20);
+}
+
+#[test]
+fn reset_empties_the_grid() {
+    let mut grid = grid();
+    grid.insert(Entity::from_raw(1), Vec2::ZERO, 10.);
+
+    grid.reset(&Arena::new(400., 400.), 80.);
+
+    assert_eq!(grid.candidates(Vec2::ZERO, 100.).count(), 0);
+}
@@ -0,0 +1,66 @@
+use asteroids::{AsteroidSize, HyperspaceSettings, Ship, ShipState, Thrusting};
+use bevy::{input::keyboard::Key, prelude::*};
+
+mod common;
+
+use common::*;
+
+fn hold_thrust(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyW);
+}
+
+// Whether there is a ship, and its engine is firing
+fn thrusting(app: &mut App) -> bool {
+    app.world_mut()
+        .query_filtered::<&Thrusting, With<Ship>>()
+        .iter(app.world())
+        .any(|thrusting| thrusting.0)
+}
+
+#[test]
+fn engine_fires_while_thrust_is_held() {
+    let mut app = new_game();
+    advance(&mut app, 2);
+    assert!(!thrusting(&mut app));
+
+    hold_thrust(&mut app);
+    advance(&mut app, 2);
+    assert!(thrusting(&mut app));
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::KeyW);
+    advance(&mut app, 2);
+    assert!(!thrusting(&mut app));
+}
+
+#[test]
+fn engine_stops_in_hyperspace() {
+    let mut app = new_game();
+    app.world_mut()
+        .resource_mut::<HyperspaceSettings>()
+        .explode_chance = 0.;
+    hold_thrust(&mut app);
+    advance(&mut app, 2);
+
+    tap(&mut app, KeyCode::KeyS, Key::Character("s".into()));
+    advance(&mut app, 2);
+
+    assert_eq!(ship_state(&app), ShipState::Hyperspace);
+    assert!(!thrusting(&mut app));
+}
+
+#[test]
+fn engine_stops_when_the_ship_is_destroyed() {
+    let mut app = new_game();
+    hold_thrust(&mut app);
+    advance(&mut app, 2);
+
+    spawn_asteroid(&mut app, Vec2::ZERO, AsteroidSize::Large);
+    advance(&mut app, 2);
+
+    assert_eq!(ship_state(&app), ShipState::Destroyed);
+    assert!(!thrusting(&mut app));
+}
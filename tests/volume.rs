@@ -0,0 +1,59 @@
+use asteroids::{Asteroids, Settings, SoundChannel, VolumeMixer};
+
+mod common;
+
+use common::*;
+
+#[test]
+fn channels_are_scaled_by_the_master_volume() {
+    let mixer = VolumeMixer {
+        master: 0.5,
+        effects: 0.8,
+        music: 0.2,
+        ..Default::default()
+    };
+
+    assert_eq!(mixer.volume(SoundChannel::Master), 0.5);
+    assert_eq!(mixer.volume(SoundChannel::Effects), 0.4);
+    assert_eq!(mixer.volume(SoundChannel::Music), 0.1);
+}
+
+#[test]
+fn muting_silences_every_channel() {
+    let mixer = VolumeMixer {
+        muted: true,
+        ..Default::default()
+    };
+
+    for channel in SoundChannel::ALL {
+        assert_eq!(mixer.volume(channel), 0.);
+    }
+}
+
+#[test]
+fn changed_volume_is_saved() {
+    let path = scratch_file("volume.ron");
+    let mut app = new_game_with(Asteroids::headless(ARENA).with_seed(0).with_settings(&path));
+
+    app.world_mut()
+        .resource_mut::<VolumeMixer>()
+        .set_level(SoundChannel::Music, 0.3);
+    app.update();
+
+    let saved = Settings::load(&path).unwrap();
+    assert_eq!(saved.volume.music, 0.3);
+    assert_eq!(saved.volume.effects, 1.);
+}
+
+#[test]
+fn distance_attenuation_is_saved() {
+    let path = scratch_file("attenuation.ron");
+    let mut app = new_game_with(Asteroids::headless(ARENA).with_seed(0).with_settings(&path));
+
+    app.world_mut()
+        .resource_mut::<VolumeMixer>()
+        .distance_attenuation = true;
+    app.update();
+
+    assert!(Settings::load(&path).unwrap().volume.distance_attenuation);
+}
@@ -0,0 +1,163 @@
+use asteroids::{
+    AsteroidSize, Asteroids, DifficultyCurve, GameState, Heartbeat, Ramp, Velocity, Wave,
+    WaveReached,
+};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+fn wave(app: &App) -> Wave {
+    *app.world().resource::<Wave>()
+}
+
+#[test]
+fn clearing_the_arena_starts_the_next_wave() {
+    let difficulty = DifficultyCurve {
+        asteroids: Ramp {
+            first: 3.,
+            per_wave: 2.,
+            max: 20.,
+        },
+        asteroid_speed: Ramp {
+            first: 50.,
+            per_wave: 25.,
+            max: 500.,
+        },
+    };
+    let mut app = new_game_with(
+        Asteroids::headless(ARENA)
+            .with_seed(0)
+            .with_difficulty(difficulty),
+    );
+    assert_eq!(wave(&app), Wave(1));
+
+    // the arena is already cleared, the next wave is announced and arrives four seconds later
+    advance(&mut app, 2);
+    assert_eq!(wave(&app), Wave(2));
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 0);
+
+    advance(&mut app, 64 * 4);
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 5);
+
+    let speeds: Vec<f32> = app
+        .world_mut()
+        .query::<&Velocity>()
+        .iter(app.world())
+        .filter(|velocity| velocity.0.length() > 0.)
+        .map(|velocity| velocity.0.length())
+        .collect();
+    assert!(
+        speeds.iter().all(|speed| (*speed - 75.).abs() < 0.01),
+        "{speeds:?}"
+    );
+}
+
+#[test]
+fn ramps_stop_at_their_max() {
+    let ramp = Ramp {
+        first: 4.,
+        per_wave: 2.,
+        max: 9.,
+    };
+
+    assert_eq!(ramp.at(Wave(1)), 4.);
+    assert_eq!(ramp.at(Wave(2)), 6.);
+    assert_eq!(ramp.at(Wave(3)), 8.);
+    assert_eq!(ramp.at(Wave(4)), 9.);
+    assert_eq!(ramp.at(Wave(40)), 9.);
+}
+
+#[test]
+fn bundled_difficulty_file_matches_the_default() {
+    let curve = DifficultyCurve::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/difficulty.ron"
+    ))
+    .unwrap();
+
+    assert_eq!(curve, DifficultyCurve::default());
+}
+
+fn beat_interval(app: &App) -> std::time::Duration {
+    app.world().resource::<Heartbeat>().interval()
+}
+
+#[test]
+fn beat_speeds_up_over_the_wave() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 200.), AsteroidSize::Large);
+    advance(&mut app, 1);
+    let start = beat_interval(&app);
+
+    advance(&mut app, 64 * 30);
+
+    assert!(beat_interval(&app) < start);
+}
+
+#[test]
+fn beat_speeds_up_as_asteroids_are_destroyed() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(200., 200.), AsteroidSize::Large);
+    let target = spawn_asteroid(&mut app, Vec2::new(-200., 200.), AsteroidSize::Large);
+    advance(&mut app, 1);
+    let start = beat_interval(&app);
+
+    app.world_mut().entity_mut(target).despawn_recursive();
+    advance(&mut app, 1);
+
+    assert!(beat_interval(&app) < start.mul_f32(0.7));
+}
+
+#[test]
+fn beat_starts_over_every_wave() {
+    let mut app = new_game();
+    let asteroid = spawn_asteroid(&mut app, Vec2::new(200., 200.), AsteroidSize::Small);
+    advance(&mut app, 64 * 30);
+    let sped_up = beat_interval(&app);
+
+    app.world_mut().entity_mut(asteroid).despawn_recursive();
+    advance(&mut app, 64 * 5);
+
+    assert_eq!(wave(&app), Wave(2));
+    assert!(beat_interval(&app) > sped_up);
+}
+
+#[test]
+fn a_wave_is_only_reached_once_its_asteroids_arrive() {
+    let mut app = new_game();
+    advance(&mut app, 2);
+    assert_eq!(wave(&app), Wave(2));
+    assert_eq!(*app.world().resource::<WaveReached>(), WaveReached(1));
+
+    advance(&mut app, 64 * 4);
+    assert_eq!(*app.world().resource::<WaveReached>(), WaveReached(2));
+}
+
+#[test]
+fn menu_asteroids_are_the_first_wave_again() {
+    let difficulty = DifficultyCurve {
+        asteroids: Ramp {
+            first: 3.,
+            per_wave: 2.,
+            max: 20.,
+        },
+        ..default()
+    };
+    let mut app = new_game_with(
+        Asteroids::headless(ARENA)
+            .with_seed(0)
+            .with_difficulty(difficulty),
+    );
+    advance(&mut app, 2 + 64 * 4);
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 5);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+
+    assert_eq!(wave(&app), Wave(1));
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Large), 3);
+}
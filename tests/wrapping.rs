@@ -0,0 +1,85 @@
+use asteroids::{Arena, AsteroidSize, BulletSettings};
+use bevy::prelude::*;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn arena_wraps_straight_across() {
+    let arena = Arena::new(800., 600.);
+
+    assert_eq!(arena.wrap(Vec2::new(410., 100.)), Vec2::new(-390., 100.));
+    assert_eq!(arena.wrap(Vec2::new(-410., -100.)), Vec2::new(390., -100.));
+    assert_eq!(arena.wrap(Vec2::new(50., 310.)), Vec2::new(50., -290.));
+    assert_eq!(arena.wrap(Vec2::new(-50., -310.)), Vec2::new(-50., 290.));
+    assert_eq!(arena.wrap(Vec2::new(10., 20.)), Vec2::new(10., 20.));
+}
+
+#[test]
+fn nearest_image_crosses_the_seam() {
+    let arena = Arena::new(800., 600.);
+
+    assert_eq!(
+        arena.nearest_image(Vec2::new(390., 0.), Vec2::new(-390., 0.)),
+        Vec2::new(410., 0.)
+    );
+    assert_eq!(
+        arena.nearest_image(Vec2::new(0., 0.), Vec2::new(100., 0.)),
+        Vec2::new(100., 0.)
+    );
+}
+
+#[test]
+fn asteroid_reappears_on_the_opposite_edge_at_the_same_height() {
+    let mut app = new_game();
+    let asteroid = spawn_moving_asteroid(
+        &mut app,
+        Vec2::new(390., 100.),
+        Vec2::new(192., 0.),
+        AsteroidSize::Small,
+    );
+
+    advance(&mut app, 10);
+
+    let translation = app.world().get::<Transform>(asteroid).unwrap().translation;
+    assert!(translation.x < -350., "{translation}");
+    assert_eq!(translation.y, 100.);
+}
+
+#[test]
+fn bullets_leave_the_arena_when_wrapping_is_off() {
+    let mut app = new_game();
+    app.world_mut().resource_mut::<BulletSettings>().wrap = false;
+
+    fire(&mut app);
+    advance(&mut app, 40);
+
+    let bullets = bullet_translations(&mut app);
+    assert_eq!(bullets.len(), 1);
+    assert!(bullets[0].y > ARENA.height / 2.);
+}
+
+#[test]
+fn bullets_wrap_by_default() {
+    let mut app = new_game();
+
+    fire(&mut app);
+    advance(&mut app, 40);
+
+    let bullets = bullet_translations(&mut app);
+    assert_eq!(bullets.len(), 1);
+    assert!(bullets[0].y < 0.);
+    assert_eq!(bullets[0].x, 0.);
+}
+
+#[test]
+fn bullet_hits_asteroid_across_the_seam() {
+    let mut app = new_game();
+    spawn_asteroid(&mut app, Vec2::new(395., -100.), AsteroidSize::Small);
+    spawn_bullet(&mut app, Vec2::new(-398., -100.), Vec2::NEG_X);
+
+    advance(&mut app, 3);
+
+    assert_eq!(asteroid_count(&mut app, AsteroidSize::Small), 0);
+}